@@ -0,0 +1,88 @@
+// Counts heap allocations made while parsing large generated graphs and checks parsing
+// allocates a bounded amount per statement
+// Run with: cargo bench --bench allocations
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dot_parser::{parser::parse, tokenizer::tokenize};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+// Graph sizes measured, BASELINE_PARSE_ALLOCATIONS is for the first one
+const EDGE_COUNTS: [usize; 2] = [2_000, 20_000];
+// Parse allocations for 2k edges when every parser step copied all the tokens after it
+const BASELINE_PARSE_ALLOCATIONS: usize = 56_048_013;
+// Tokens are copied into the parse buffer once, after that each edge statement only allocates
+// its own AST. More than this per statement means something copies the buffer again
+const MAX_PARSE_ALLOCATIONS_PER_STATEMENT: usize = 12;
+
+fn generate_dot(edges: usize) -> String {
+    let mut code = String::from("strict digraph big {\n");
+    for idx in 0..edges {
+        code.push_str(&format!(
+            "    n{} -> n{} [label=\"edge {}\"];\n",
+            idx,
+            idx + 1,
+            idx
+        ));
+    }
+    code.push('}');
+    code
+}
+
+fn main() {
+    let empty_tokens = tokenize("strict digraph big {}".to_string()).unwrap();
+    let (_, head_allocations) = count_allocations(|| parse(&empty_tokens).unwrap());
+    println!("empty graph parse allocations: {}", head_allocations);
+
+    for (idx, edges) in EDGE_COUNTS.into_iter().enumerate() {
+        let code = generate_dot(edges);
+        let (tokens, tokenize_allocations) = count_allocations(|| tokenize(code.clone()).unwrap());
+        let (graph, parse_allocations) = count_allocations(|| parse(&tokens).unwrap());
+        let statements = graph.statements.map_or(0, |s| s.len());
+
+        println!("{} edges", edges);
+        println!("  tokens: {}", tokens.len());
+        println!("  tokenize allocations: {}", tokenize_allocations);
+        println!(
+            "  parse allocations: {} ({} per statement)",
+            parse_allocations,
+            parse_allocations / statements
+        );
+        if idx == 0 {
+            println!(
+                "  baseline parse allocations: {} ({}x more)",
+                BASELINE_PARSE_ALLOCATIONS,
+                BASELINE_PARSE_ALLOCATIONS / parse_allocations
+            );
+        }
+        assert!(
+            parse_allocations
+                <= head_allocations + statements * MAX_PARSE_ALLOCATIONS_PER_STATEMENT,
+            "parse allocations grew past {} per statement",
+            MAX_PARSE_ALLOCATIONS_PER_STATEMENT
+        );
+    }
+}
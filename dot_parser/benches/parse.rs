@@ -0,0 +1,65 @@
+// Tokenize and parse timings on generated graphs of growing size
+// Run with: cargo bench --bench parse
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use dot_parser::{parser::parse, tokenizer::tokenize};
+
+const EDGE_COUNTS: [usize; 4] = [100, 1_000, 10_000, 100_000];
+
+// A valid digraph with `edges` edges over roughly edges / 4 nodes, mixing the statement kinds
+// real files use: attribute statements, node declarations, attributed edges and subgraphs
+fn generate_dot(edges: usize) -> String {
+    let nodes = (edges / 4).max(2);
+    let mut code = String::from("digraph generated {\n    rankdir=LR;\n    node [shape=box];\n");
+    for idx in 0..nodes {
+        code.push_str(&format!("    n{} [label=\"node {}\"];\n", idx, idx));
+    }
+    for idx in 0..edges {
+        let from = idx % nodes;
+        let to = (idx * 7 + 1) % nodes;
+        if idx % 100 == 0 {
+            code.push_str(&format!(
+                "    subgraph cluster_{} {{ n{} -> n{} }}\n",
+                idx, from, to
+            ));
+        } else {
+            code.push_str(&format!(
+                "    n{} -> n{} [weight={}];\n",
+                from,
+                to,
+                idx % 10
+            ));
+        }
+    }
+    code.push('}');
+    code
+}
+
+fn bench_tokenize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+    group.sample_size(10);
+    for edges in EDGE_COUNTS {
+        let code = generate_dot(edges);
+        group.throughput(Throughput::Bytes(code.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(edges), &code, |b, code| {
+            b.iter(|| tokenize(black_box(code.clone())).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    for edges in EDGE_COUNTS {
+        let tokens = tokenize(generate_dot(edges)).unwrap();
+        group.throughput(Throughput::Elements(edges as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(edges), &tokens, |b, tokens| {
+            b.iter(|| parse(black_box(tokens)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tokenize, bench_parse);
+criterion_main!(benches);
@@ -0,0 +1,618 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::{
+    graph::Edge,
+    parser::grammer::{DotGraph, NodeId},
+};
+
+// Union-find root of `node`, halving the path on the way up
+fn root(parent: &mut [usize], mut node: usize) -> usize {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+// Joins the sets of `a` and `b`, false when they already were one set
+fn union(parent: &mut [usize], a: usize, b: usize) -> bool {
+    let (a, b) = (root(parent, a), root(parent, b));
+    if a == b {
+        return false;
+    }
+    parent[a.max(b)] = a.min(b);
+    true
+}
+
+impl DotGraph {
+    // Out-neighbors of every node (all neighbors for undirected graphs), sorted and without repeats
+    // Nodes without edges are listed with no neighbors
+    pub fn adjacency_list(&self) -> BTreeMap<String, Vec<String>> {
+        let mut adjacency: BTreeMap<String, Vec<String>> = self
+            .node_ids()
+            .iter()
+            .map(|node_id| (node_id.id.clone(), vec![]))
+            .collect();
+        for edge in self.edges() {
+            let mut link = |from: &str, to: &str| {
+                adjacency
+                    .entry(from.to_string())
+                    .or_default()
+                    .push(to.to_string())
+            };
+            link(&edge.from.id, &edge.to.id);
+            if !self.is_directed() && edge.from.id != edge.to.id {
+                link(&edge.to.id, &edge.from.id);
+            }
+        }
+        for neighbors in adjacency.values_mut() {
+            neighbors.sort();
+            neighbors.dedup();
+        }
+        adjacency
+    }
+
+    // Sources of the edges pointing at `id`, in document order without repeats
+    // Undirected edges have no direction, so undirected graphs give no predecessors
+    pub fn predecessors(&self, id: &str) -> Vec<&str> {
+        self.directed_neighbors(id, |edge| (edge.to, edge.from))
+    }
+
+    // Targets of the edges leaving `id`, empty for undirected graphs like predecessors
+    pub fn successors(&self, id: &str) -> Vec<&str> {
+        self.directed_neighbors(id, |edge| (edge.from, edge.to))
+    }
+
+    fn directed_neighbors<'a>(
+        &'a self,
+        id: &str,
+        ends: fn(Edge<'a>) -> (&'a NodeId, &'a NodeId),
+    ) -> Vec<&'a str> {
+        let mut neighbors: Vec<&str> = vec![];
+        if !self.is_directed() {
+            return neighbors;
+        }
+        for edge in self.edges() {
+            let (node, neighbor) = ends(edge);
+            if node.id == id && !neighbors.contains(&neighbor.id.as_str()) {
+                neighbors.push(&neighbor.id);
+            }
+        }
+        neighbors
+    }
+
+    // Node ids grouped by (weakly) connected component, components and their nodes
+    // in first-mention order
+    pub(crate) fn connected_components(&self) -> Vec<Vec<String>> {
+        let ids = self.node_ids_in_order();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+        let mut parent: Vec<usize> = (0..ids.len()).collect();
+        for edge in self.edges() {
+            union(
+                &mut parent,
+                index[edge.from.id.as_str()],
+                index[edge.to.id.as_str()],
+            );
+        }
+
+        let mut components: Vec<Vec<String>> = vec![];
+        let mut component_of: HashMap<usize, usize> = HashMap::new();
+        for (idx, id) in ids.into_iter().enumerate() {
+            let component = *component_of
+                .entry(root(&mut parent, idx))
+                .or_insert_with(|| {
+                    components.push(vec![]);
+                    components.len() - 1
+                });
+            components[component].push(id);
+        }
+        components
+    }
+
+    // Components when edge directions are ignored, same order as connected_components
+    pub fn weakly_connected_components(&self) -> Vec<Vec<String>> {
+        self.connected_components()
+    }
+
+    // Groups of nodes that can all reach each other (Tarjan's algorithm), undirected edges
+    // go both ways. Nodes within a component and components by first mention of their nodes
+    pub fn strongly_connected_components(&self) -> Vec<Vec<String>> {
+        let ids = self.node_ids_in_order();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+        let mut adjacency: Vec<Vec<usize>> = vec![vec![]; ids.len()];
+        for edge in self.edges() {
+            let (from, to) = (index[edge.from.id.as_str()], index[edge.to.id.as_str()]);
+            adjacency[from].push(to);
+            if !self.is_directed() {
+                adjacency[to].push(from);
+            }
+        }
+
+        // explicit call stack of (node, next neighbor to look at), long chains would overflow
+        let mut order: Vec<Option<usize>> = vec![None; ids.len()];
+        let mut lowlink: Vec<usize> = vec![0; ids.len()];
+        let mut on_stack: Vec<bool> = vec![false; ids.len()];
+        let mut stack: Vec<usize> = vec![];
+        let mut next_order = 0;
+        let mut components: Vec<Vec<usize>> = vec![];
+        for start in 0..ids.len() {
+            if order[start].is_some() {
+                continue;
+            }
+            let mut call_stack: Vec<(usize, usize)> = vec![(start, 0)];
+            order[start] = Some(next_order);
+            lowlink[start] = next_order;
+            next_order += 1;
+            stack.push(start);
+            on_stack[start] = true;
+            while let Some(&(node, next_neighbor)) = call_stack.last() {
+                if let Some(&neighbor) = adjacency[node].get(next_neighbor) {
+                    if let Some(frame) = call_stack.last_mut() {
+                        frame.1 += 1;
+                    }
+                    match order[neighbor] {
+                        None => {
+                            order[neighbor] = Some(next_order);
+                            lowlink[neighbor] = next_order;
+                            next_order += 1;
+                            stack.push(neighbor);
+                            on_stack[neighbor] = true;
+                            call_stack.push((neighbor, 0));
+                        }
+                        Some(neighbor_order) if on_stack[neighbor] => {
+                            lowlink[node] = lowlink[node].min(neighbor_order);
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[node]);
+                }
+                if Some(lowlink[node]) == order[node] {
+                    let mut component = vec![];
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+        components.sort_unstable_by_key(|component| component[0]);
+        components
+            .into_iter()
+            .map(|component| component.into_iter().map(|idx| ids[idx].clone()).collect())
+            .collect()
+    }
+
+    // Edges of a spanning forest, one tree per connected component, picked in document order
+    // Digraphs are treated as undirected, tree edges keep the direction they were written in
+    pub fn spanning_tree(&self) -> Vec<(String, String)> {
+        let ids = self.node_ids_in_order();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+        let mut parent: Vec<usize> = (0..ids.len()).collect();
+        self.edges()
+            .into_iter()
+            .filter(|edge| {
+                union(
+                    &mut parent,
+                    index[edge.from.id.as_str()],
+                    index[edge.to.id.as_str()],
+                )
+            })
+            .map(|edge| (edge.from.id.clone(), edge.to.id.clone()))
+            .collect()
+    }
+
+    // No cycles when edge directions are ignored: every edge makes it into the spanning forest
+    // Self loops and repeated edges are cycles
+    pub fn is_forest(&self) -> bool {
+        self.spanning_tree().len() == self.edge_count()
+    }
+
+    // A forest with exactly one component, so the empty graph is not a tree
+    pub fn is_tree(&self) -> bool {
+        self.is_forest() && self.connected_components().len() == 1
+    }
+
+    // Pairs (a, b) with a < b where both a -> b and b -> a exist, sorted
+    // Undirected graphs have no direction and give no pairs; self loops are skipped
+    pub fn bidirectional_pairs(&self) -> Vec<(String, String)> {
+        if !self.is_directed() {
+            return vec![];
+        }
+        let edges: HashSet<(&str, &str)> = self
+            .edges()
+            .iter()
+            .map(|edge| (edge.from.id.as_str(), edge.to.id.as_str()))
+            .collect();
+        let mut pairs: Vec<(String, String)> = edges
+            .iter()
+            .filter(|(from, to)| from < to && edges.contains(&(*to, *from)))
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    // Neighbor count of every node over n - 1, self loops and parallel edges not counted
+    // In digraphs in- and out-neighbors are added up, so values go up to 2.0
+    pub fn degree_centrality(&self) -> HashMap<String, f64> {
+        let adjacency = self.adjacency_list();
+        let mut degrees: HashMap<&str, usize> =
+            adjacency.keys().map(|node| (node.as_str(), 0)).collect();
+        for (node, neighbors) in &adjacency {
+            for neighbor in neighbors.iter().filter(|neighbor| *neighbor != node) {
+                *degrees.entry(node).or_default() += 1;
+                // undirected neighbors are already listed from both ends
+                if self.is_directed() {
+                    *degrees.entry(neighbor).or_default() += 1;
+                }
+            }
+        }
+        let others = adjacency.len().saturating_sub(1);
+        degrees
+            .into_iter()
+            .map(|(node, degree)| {
+                let centrality = if others == 0 {
+                    0.0
+                } else {
+                    degree as f64 / others as f64
+                };
+                (node.to_string(), centrality)
+            })
+            .collect()
+    }
+
+    // Nodes no edge points at, in document order; isolated nodes count too
+    // Undirected graphs have no direction and give no roots
+    pub fn roots(&self) -> Vec<String> {
+        self.nodes_without(|edge| &edge.to.id)
+    }
+
+    // Nodes no edge leaves from, the counterpart of roots
+    pub fn leaves(&self) -> Vec<String> {
+        self.nodes_without(|edge| &edge.from.id)
+    }
+
+    fn nodes_without<'a>(&'a self, end: fn(&Edge<'a>) -> &'a String) -> Vec<String> {
+        if !self.is_directed() {
+            return vec![];
+        }
+        let edges = self.edges();
+        let ends: HashSet<&str> = edges.iter().map(|edge| end(edge).as_str()).collect();
+        self.node_ids_in_order()
+            .into_iter()
+            .filter(|id| !ends.contains(id.as_str()))
+            .collect()
+    }
+
+    // Distinct edges over the most the graph could have; self loops and parallel edges are not counted
+    pub fn density(&self) -> f64 {
+        let nodes = self.node_ids().len();
+        if nodes < 2 {
+            return 0.0;
+        }
+        let edges: HashSet<(String, String)> = self
+            .edges()
+            .iter()
+            .filter(|edge| edge.from.id != edge.to.id)
+            .map(|edge| self.edge_key(&edge.from.id, &edge.to.id))
+            .collect();
+        let mut possible = (nodes * (nodes - 1)) as f64;
+        if !self.is_directed() {
+            possible /= 2.0;
+        }
+        edges.len() as f64 / possible
+    }
+
+    // Layer of every node for a top to bottom layout: sources are rank 0 and every other
+    // node sits one below its deepest predecessor. None when the edges contain a cycle,
+    // which includes any edge of an undirected graph
+    pub fn ranks(&self) -> Option<BTreeMap<String, usize>> {
+        let adjacency = self.adjacency_list();
+        let mut in_degree: BTreeMap<&str, usize> =
+            adjacency.keys().map(|node| (node.as_str(), 0)).collect();
+        for neighbors in adjacency.values() {
+            for neighbor in neighbors {
+                *in_degree.entry(neighbor).or_default() += 1;
+            }
+        }
+
+        let mut ranks: BTreeMap<String, usize> = BTreeMap::new();
+        let mut queue: VecDeque<&str> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(node, _)| *node)
+            .collect();
+        while let Some(node) = queue.pop_front() {
+            let rank = *ranks.entry(node.to_string()).or_insert(0);
+            for neighbor in &adjacency[node] {
+                let neighbor_rank = ranks.entry(neighbor.clone()).or_insert(0);
+                *neighbor_rank = (*neighbor_rank).max(rank + 1);
+                let degree = in_degree.get_mut(neighbor.as_str())?;
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        if in_degree.values().any(|degree| *degree > 0) {
+            return None;
+        }
+        Some(ranks)
+    }
+
+    // Number of edges on the longest path, None when the graph is not a DAG
+    pub fn longest_path_len(&self) -> Option<usize> {
+        Some(self.ranks()?.into_values().max().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    fn graph(code: &str) -> DotGraph {
+        parse(&tokenize(code.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_density_complete_graphs() {
+        assert_eq!(graph("graph { a -- b -- c -- a }").density(), 1.0);
+        assert_eq!(graph("digraph { a -> b -> c -> a }").density(), 0.5);
+        assert_eq!(
+            graph("digraph { a -> {b c}; b -> {a c}; c -> {a b}; a -> b }").density(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_density_degenerate_graphs() {
+        assert_eq!(graph("graph { }").density(), 0.0);
+        assert_eq!(graph("graph { a -- a }").density(), 0.0);
+        assert_eq!(graph("graph { a; b }").density(), 0.0);
+    }
+
+    fn adjacency(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(node, neighbors)| {
+                (
+                    node.to_string(),
+                    neighbors.iter().map(|n| n.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_adjacency_list_directed() {
+        let dg = graph("digraph { d; c -> a; a -> {c b}; a -> b }");
+        assert_eq!(
+            dg.adjacency_list(),
+            adjacency(&[("a", &["b", "c"]), ("b", &[]), ("c", &["a"]), ("d", &[])])
+        );
+    }
+
+    #[test]
+    fn test_adjacency_list_undirected() {
+        let dg = graph("graph { a -- b -- c; c -- c }");
+        assert_eq!(
+            dg.adjacency_list(),
+            adjacency(&[("a", &["b"]), ("b", &["a", "c"]), ("c", &["b", "c"])])
+        );
+    }
+
+    #[test]
+    fn test_longest_path_len() {
+        let diamond = graph("digraph { a -> b -> d; a -> c -> d }");
+        assert_eq!(diamond.longest_path_len(), Some(2));
+        assert_eq!(
+            diamond.ranks().unwrap(),
+            BTreeMap::from([
+                ("a".to_string(), 0),
+                ("b".to_string(), 1),
+                ("c".to_string(), 1),
+                ("d".to_string(), 2),
+            ])
+        );
+        assert_eq!(
+            graph("digraph { a -> d; a -> b -> c -> d; e }").longest_path_len(),
+            Some(3)
+        );
+        assert_eq!(graph("digraph { }").longest_path_len(), Some(0));
+    }
+
+    #[test]
+    fn test_longest_path_len_with_cycle() {
+        assert_eq!(
+            graph("digraph { a -> b -> c -> a }").longest_path_len(),
+            None
+        );
+        assert_eq!(graph("digraph { a -> b; b -> b }").longest_path_len(), None);
+        assert_eq!(graph("graph { a -- b }").longest_path_len(), None);
+    }
+
+    #[test]
+    fn test_predecessors_and_successors() {
+        let dg = graph("digraph { a -> b; c -> b; b -> d; a -> b }");
+        assert_eq!(dg.predecessors("b"), vec!["a", "c"]);
+        assert_eq!(dg.successors("b"), vec!["d"]);
+        assert_eq!(dg.successors("a"), vec!["b"]);
+        assert!(dg.predecessors("a").is_empty());
+        assert!(dg.successors("missing").is_empty());
+
+        let undirected = graph("graph { a -- b; c -- b }");
+        assert!(undirected.predecessors("b").is_empty());
+        assert!(undirected.successors("a").is_empty());
+    }
+
+    #[test]
+    fn test_spanning_tree() {
+        let dg = graph("graph { a -- b -- c -- a; c -- d; d -- d; e -- f; f -- e; g }");
+        let tree = dg.spanning_tree();
+        // 7 nodes in 3 components
+        assert_eq!(tree.len(), 4);
+        assert_eq!(
+            tree,
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("b".to_string(), "c".to_string()),
+                ("c".to_string(), "d".to_string()),
+                ("e".to_string(), "f".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spanning_tree_of_digraph_ignores_direction() {
+        let dg = graph("digraph { a -> b; c -> b; b -> a }");
+        assert_eq!(
+            dg.spanning_tree(),
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("c".to_string(), "b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_forest_and_is_tree() {
+        let tree = graph("digraph { r -> a; r -> b; b -> c; b -> d }");
+        assert!(tree.is_forest() && tree.is_tree());
+        let forest = graph("graph { a -- b; c -- d -- e; f }");
+        assert!(forest.is_forest() && !forest.is_tree());
+        // a -> b -> c <- a is a cycle once directions are ignored
+        let cycle = graph("digraph { a -> b -> c; a -> c }");
+        assert!(!cycle.is_forest() && !cycle.is_tree());
+        assert!(!graph("graph { a -- a }").is_forest());
+        assert!(!graph("graph { a -- b; b -- a }").is_forest());
+        assert!(graph("graph { a }").is_tree());
+        assert!(graph("graph { }").is_forest() && !graph("graph { }").is_tree());
+    }
+
+    #[test]
+    fn test_weakly_and_strongly_connected_components() {
+        // a -> b -> c -> a is a cycle, c -> d is a bridge out of it
+        let dg = graph("digraph { a -> b -> c -> a; c -> d; x -> y; y -> x; z }");
+        let components = |groups: &[&[&str]]| -> Vec<Vec<String>> {
+            groups
+                .iter()
+                .map(|group| group.iter().map(|id| id.to_string()).collect())
+                .collect()
+        };
+        assert_eq!(
+            dg.weakly_connected_components(),
+            components(&[&["a", "b", "c", "d"], &["x", "y"], &["z"]])
+        );
+        assert_eq!(
+            dg.strongly_connected_components(),
+            components(&[&["a", "b", "c"], &["d"], &["x", "y"], &["z"]])
+        );
+
+        let chain = graph("digraph { a -> b -> c }");
+        assert_eq!(chain.weakly_connected_components().len(), 1);
+        assert_eq!(chain.strongly_connected_components().len(), 3);
+        let undirected = graph("graph { a -- b -- c; d }");
+        assert_eq!(
+            undirected.strongly_connected_components(),
+            components(&[&["a", "b", "c"], &["d"]])
+        );
+        assert!(graph("graph { }")
+            .strongly_connected_components()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_bidirectional_pairs() {
+        let dg = graph("digraph { a -> b; b -> a; a -> c }");
+        assert_eq!(
+            dg.bidirectional_pairs(),
+            vec![("a".to_string(), "b".to_string())]
+        );
+
+        let dg = graph("digraph { d -> c; c -> d; b -> a -> b; a -> b; e -> e }");
+        assert_eq!(
+            dg.bidirectional_pairs(),
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("c".to_string(), "d".to_string()),
+            ]
+        );
+        assert!(graph("graph { a -- b; b -- a }")
+            .bidirectional_pairs()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_degree_centrality_star() {
+        let centrality = graph("graph { c -- {a b d e}; a -- c }").degree_centrality();
+        assert_eq!(centrality["c"], 1.0);
+        assert_eq!(centrality["a"], 0.25);
+        assert_eq!(centrality.len(), 5);
+
+        let centrality = graph("digraph { c -> {a b d}; a -> b; c -> c }").degree_centrality();
+        assert_eq!(centrality["c"], 1.0);
+        assert_eq!(centrality["a"], 2.0 / 3.0);
+        assert_eq!(centrality["d"], 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_degree_centrality_single_node() {
+        let centrality = graph("graph { a }").degree_centrality();
+        assert_eq!(centrality["a"], 0.0);
+        assert!(graph("graph { }").degree_centrality().is_empty());
+    }
+
+    #[test]
+    fn test_quoted_ids_with_edge_ops_are_one_node() {
+        let dg = graph("digraph { \"a->b\" -> c; a -> \"a->b\"; \"x--y\" }");
+        assert_eq!(dg.node_count(), 4);
+        assert_eq!(dg.edge_count(), 2);
+        assert_eq!(
+            dg.adjacency_list(),
+            adjacency(&[
+                ("a", &["a->b"]),
+                ("a->b", &["c"]),
+                ("c", &[]),
+                ("x--y", &[])
+            ])
+        );
+        assert_eq!(dg.successors("a->b"), vec!["c"]);
+        assert!(!dg.contains_node("b"));
+        assert_eq!(dg.longest_path_len(), Some(2));
+    }
+
+    #[test]
+    fn test_roots_and_leaves() {
+        let dg = graph("digraph { r -> {a b}; a -> {c d}; b -> e; lone; x -> x }");
+        assert_eq!(dg.roots(), vec!["r", "lone"]);
+        assert_eq!(dg.leaves(), vec!["c", "d", "e", "lone"]);
+
+        let undirected = graph("graph { a -- b }");
+        assert!(undirected.roots().is_empty());
+        assert!(undirected.leaves().is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Charset {
+    #[default]
+    Utf8,
+    Latin1,
+}
+
+impl Charset {
+    // Spellings graphviz accepts for the charset attribute
+    pub fn from_name(name: &str) -> Option<Charset> {
+        match name.to_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Charset::Utf8),
+            "latin1" | "latin-1" | "l1" | "iso-8859-1" | "iso_8859-1" | "iso8859-1" => {
+                Some(Charset::Latin1)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> Result<String> {
+        match self {
+            Charset::Utf8 => match String::from_utf8(bytes.to_vec()) {
+                Ok(code) => Ok(code),
+                Err(err) => bail!("Input is not valid UTF-8: {}", err),
+            },
+            // every latin1 byte is the unicode code point with the same value
+            Charset::Latin1 => Ok(bytes.iter().map(|b| *b as char).collect()),
+        }
+    }
+}
+
+fn is_id_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+// Contents of the quoted string starting at `start` and the index just past its closing quote
+// An unterminated string runs to the end of the input
+fn quoted(bytes: &[u8], start: usize) -> (&[u8], usize) {
+    let mut idx = start + 1;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' => idx += 2,
+            b'"' => return (&bytes[start + 1..idx], idx + 1),
+            _ => idx += 1,
+        }
+    }
+    (&bytes[start + 1..], bytes.len())
+}
+
+// The value of `= value` or `= "value"` at `start`, whitespace allowed around '='
+fn assigned_value(bytes: &[u8], start: usize) -> Option<&[u8]> {
+    let skip_space = |idx: usize| {
+        idx + bytes[idx..]
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count()
+    };
+    let equal = skip_space(start);
+    if bytes.get(equal) != Some(&b'=') {
+        return None;
+    }
+    let value_start = skip_space(equal + 1);
+    let value = match bytes.get(value_start) {
+        Some(b'"') => quoted(bytes, value_start).0,
+        _ => {
+            let len = bytes[value_start..]
+                .iter()
+                .take_while(|b| is_id_byte(**b) || **b == b'-')
+                .count();
+            &bytes[value_start..value_start + len]
+        }
+    };
+    Some(value)
+}
+
+// Looks for `charset = value` in the raw bytes without decoding them first
+// The attribute name and value are ascii, so this works for both supported charsets
+// Only a whole `charset` id outside of quoted strings and comments counts, the last one
+// with a recognized value wins
+pub fn detect_charset(bytes: &[u8]) -> Option<Charset> {
+    let key = b"charset";
+    let mut charset = None;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let rest = &bytes[idx..];
+        if rest.starts_with(b"//") || (rest[0] == b'#' && (idx == 0 || bytes[idx - 1] == b'\n')) {
+            idx += rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        } else if rest.starts_with(b"/*") {
+            idx += rest[2..]
+                .windows(2)
+                .position(|window| window == b"*/")
+                .map_or(rest.len(), |end| end + 4);
+        } else if rest[0] == b'"' {
+            let (contents, end) = quoted(bytes, idx);
+            // a quoted "charset" is the same id as charset
+            if contents == key {
+                if let Some(value) = assigned_value(bytes, end) {
+                    charset = Charset::from_name(&String::from_utf8_lossy(value)).or(charset);
+                }
+            }
+            idx = end;
+        } else if is_id_byte(rest[0]) {
+            let len = rest.iter().take_while(|b| is_id_byte(**b)).count();
+            if &rest[..len] == key {
+                if let Some(value) = assigned_value(bytes, idx + len) {
+                    charset = Charset::from_name(&String::from_utf8_lossy(value)).or(charset);
+                }
+            }
+            idx += len;
+        } else {
+            idx += 1;
+        }
+    }
+    charset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_charset() {
+        assert_eq!(
+            detect_charset(b"graph { charset=\"latin1\" }"),
+            Some(Charset::Latin1)
+        );
+        assert_eq!(
+            detect_charset(b"graph { charset = UTF-8; }"),
+            Some(Charset::Utf8)
+        );
+        assert_eq!(detect_charset(b"graph { a -- b }"), None);
+        assert_eq!(detect_charset(b"graph { charset=klingon }"), None);
+        assert_eq!(
+            detect_charset(b"graph { \"charset\"=l1 }"),
+            Some(Charset::Latin1)
+        );
+    }
+
+    #[test]
+    fn test_detect_charset_checks_every_occurrence() {
+        assert_eq!(
+            detect_charset(b"graph { a [label=\"charset\"]; charset=latin1 }"),
+            Some(Charset::Latin1)
+        );
+        assert_eq!(
+            detect_charset(b"graph { mycharset=x; charset=latin1 }"),
+            Some(Charset::Latin1)
+        );
+        assert_eq!(
+            detect_charset(b"graph { charset=latin1; charset=utf8 }"),
+            Some(Charset::Utf8)
+        );
+    }
+
+    #[test]
+    fn test_detect_charset_ignores_quoted_strings_and_comments() {
+        for code in [
+            "graph { a [label=\"charset=latin1\"] }",
+            "graph { a [label=\"say \\\"charset=latin1\\\"\"] }",
+            "graph { // charset=latin1\n a }",
+            "graph { /* charset=latin1 */ a }",
+            "# charset=latin1\ngraph { a }",
+        ] {
+            assert_eq!(detect_charset(code.as_bytes()), None, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(Charset::Latin1.decode(b"caf\xe9").unwrap(), "café");
+        assert!(Charset::Utf8.decode(b"caf\xe9").is_err());
+    }
+}
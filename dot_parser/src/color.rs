@@ -0,0 +1,251 @@
+use crate::parser::grammer::{Attribute, DotGraph, EdgeStmtSide, Statement};
+
+// Attributes whose value is a single color
+const COLOR_ATTRIBUTES: [&str; 6] = [
+    "color",
+    "fillcolor",
+    "fontcolor",
+    "bgcolor",
+    "pencolor",
+    "labelfontcolor",
+];
+
+// A subset of the X11 color scheme graphviz uses by default
+// "transparent" is left out, it has no opaque #rrggbb spelling and is kept as written
+const NAMED_COLORS: [(&str, (u8, u8, u8)); 23] = [
+    ("black", (0, 0, 0)),
+    ("white", (255, 255, 255)),
+    ("red", (255, 0, 0)),
+    ("green", (0, 255, 0)),
+    ("blue", (0, 0, 255)),
+    ("yellow", (255, 255, 0)),
+    ("cyan", (0, 255, 255)),
+    ("magenta", (255, 0, 255)),
+    ("gray", (192, 192, 192)),
+    ("grey", (192, 192, 192)),
+    ("lightgray", (211, 211, 211)),
+    ("lightgrey", (211, 211, 211)),
+    ("darkgray", (169, 169, 169)),
+    ("darkgrey", (169, 169, 169)),
+    ("orange", (255, 165, 0)),
+    ("purple", (160, 32, 240)),
+    ("pink", (255, 192, 203)),
+    ("brown", (165, 42, 42)),
+    ("navy", (0, 0, 128)),
+    ("gold", (255, 215, 0)),
+    ("lightblue", (173, 216, 230)),
+    ("darkgreen", (0, 100, 0)),
+    ("crimson", (220, 20, 60)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    // 255 is opaque
+    pub a: u8,
+}
+
+impl Color {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    // #rrggbb, or #rrggbbaa when the color is not opaque
+    pub fn to_hex(&self) -> String {
+        let rgb = format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b);
+        match self.a {
+            255 => rgb,
+            a => format!("{}{:02x}", rgb, a),
+        }
+    }
+}
+
+fn parse_hex(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+    let color = Color::new(channel(0)?, channel(2)?, channel(4)?);
+    match hex.len() {
+        8 => Some(color.with_alpha(channel(6)?)),
+        _ => Some(color),
+    }
+}
+
+// "h,s,v" or "h s v", every component in 0..=1
+fn parse_hsv(value: &str) -> Option<Color> {
+    let components: Vec<f64> = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<f64>().ok())
+        .collect::<Option<Vec<f64>>>()?;
+    let [h, s, v] = components[..] else {
+        return None;
+    };
+    if ![h, s, v].iter().all(|c| (0.0..=1.0).contains(c)) {
+        return None;
+    }
+
+    let sector = (h * 6.0).floor();
+    let fraction = h * 6.0 - sector;
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - s * fraction);
+    let t = v * (1.0 - s * (1.0 - fraction));
+    let (r, g, b) = match sector as u8 % 6 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    let to_u8 = |c: f64| (c * 255.0).round() as u8;
+    Some(Color::new(to_u8(r), to_u8(g), to_u8(b)))
+}
+
+// Recognizes named colors, #rrggbb, #rrggbbaa and "h,s,v"
+pub fn normalize_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return parse_hex(value);
+    }
+    let lower = value.to_lowercase();
+    if let Some((_, (r, g, b))) = NAMED_COLORS.iter().find(|(name, _)| *name == lower) {
+        return Some(Color::new(*r, *g, *b));
+    }
+    parse_hsv(value)
+}
+
+fn normalize_attribute(lhs: &str, rhs: &mut String) {
+    if !COLOR_ATTRIBUTES.contains(&lhs) {
+        return;
+    }
+    if let Some(color) = normalize_color(rhs) {
+        *rhs = color.to_hex();
+    }
+}
+
+fn normalize_attributes(attributes: &mut [Attribute]) {
+    for attribute in attributes {
+        normalize_attribute(&attribute.lhs, &mut attribute.rhs);
+    }
+}
+
+fn normalize_side(side: &mut EdgeStmtSide) {
+    if let EdgeStmtSide::SubGraph(subgraph) = side {
+        normalize_statements(&mut subgraph.statements);
+    }
+}
+
+fn normalize_statements(statements: &mut [Statement]) {
+    for statement in statements {
+        match statement {
+            Statement::NodeStmt(node_stmt) => {
+                normalize_attributes(node_stmt.attributes.as_deref_mut().unwrap_or_default())
+            }
+            Statement::EdgeStmt(edge_stmt) => {
+                normalize_attributes(edge_stmt.attributes.as_deref_mut().unwrap_or_default());
+                normalize_side(&mut edge_stmt.edge_lhs);
+                let mut rhs = Some(&mut edge_stmt.edge_rhs);
+                while let Some(current) = rhs {
+                    normalize_side(&mut current.edge_to);
+                    rhs = current.edge_optional.as_deref_mut();
+                }
+            }
+            Statement::AttrStmt(attr_stmt) => normalize_attributes(&mut attr_stmt.attr_list.items),
+            Statement::AttributeStmt(attribute_stmt) => {
+                normalize_attribute(&attribute_stmt.lhs, &mut attribute_stmt.rhs)
+            }
+            Statement::SubGraph(subgraph) => normalize_statements(&mut subgraph.statements),
+        }
+    }
+}
+
+impl DotGraph {
+    // Rewrites every recognized color attribute value to #rrggbb (#rrggbbaa when it has alpha),
+    // unknown values are left as is
+    pub fn normalize_colors(&mut self) {
+        if let Some(statements) = &mut self.statements {
+            normalize_statements(statements);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_named_color() {
+        assert_eq!(normalize_color("red"), Some(Color::new(255, 0, 0)));
+        assert_eq!(
+            normalize_color("LightGray"),
+            Some(Color::new(211, 211, 211))
+        );
+        assert_eq!(normalize_color("notacolor"), None);
+    }
+
+    #[test]
+    fn test_normalize_hex_color() {
+        assert_eq!(normalize_color("#1a2B3c"), Some(Color::new(26, 43, 60)));
+        assert_eq!(
+            normalize_color("#1a2b3c80"),
+            Some(Color::new(26, 43, 60).with_alpha(128))
+        );
+        assert_eq!(normalize_color("#1A2B3CFF"), Some(Color::new(26, 43, 60)));
+        assert_eq!(normalize_color("#12345"), None);
+        assert_eq!(normalize_color("#zz0000"), None);
+    }
+
+    #[test]
+    fn test_normalize_hsv_color() {
+        assert_eq!(normalize_color("0,1,1"), Some(Color::new(255, 0, 0)));
+        assert_eq!(normalize_color("0.333 1 1"), Some(Color::new(1, 255, 0)));
+        assert_eq!(
+            normalize_color("0.5, 0, 0.5"),
+            Some(Color::new(128, 128, 128))
+        );
+        assert_eq!(normalize_color("2,1,1"), None);
+        assert_eq!(normalize_color("0,1"), None);
+    }
+
+    #[test]
+    fn test_normalize_colors_in_graph() {
+        let code = "digraph { bgcolor=white; a [color=red, label=red]; a -> {b [fillcolor=\"0,1,1\"]} [color=\"#0000FF\"] }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        dg.normalize_colors();
+        assert_eq!(
+            dg.to_string(),
+            "digraph {
+    bgcolor=\"#ffffff\";
+    a [color=\"#ff0000\", label=red];
+    a -> {b [fillcolor=\"#ff0000\"]} [color=\"#0000ff\"];
+}"
+        );
+    }
+
+    #[test]
+    fn test_normalize_colors_keeps_alpha_and_transparent() {
+        assert_eq!(normalize_color("transparent"), None);
+        let code =
+            "digraph { bgcolor=transparent; a [color=\"#FF000080\", fillcolor=\"#00ff00ff\"] }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        dg.normalize_colors();
+        assert_eq!(
+            dg.to_string(),
+            "digraph {
+    bgcolor=transparent;
+    a [color=\"#ff000080\", fillcolor=\"#00ff00\"];
+}"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use crate::{
+    graph::{edge_sides, side_node_ids},
+    parser::grammer::{
+        AttrStmtKind, Attribute, DotGraph, EdgeStmt, EdgeStmtSide, NodeId, Statement,
+    },
+    transform::merge_into,
+};
+
+// A flattened edge with the `edge [...]` defaults in scope applied, its own attributes win
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedEdge {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub attributes: Vec<Attribute>,
+}
+
+// Defaults set by `node [...]` and `edge [...]`, one entry per open graph or subgraph
+#[derive(Default)]
+struct Resolver {
+    node_scopes: Vec<Vec<Attribute>>,
+    edge_scopes: Vec<Vec<Attribute>>,
+    nodes: HashMap<String, Vec<Attribute>>,
+    edges: Vec<ResolvedEdge>,
+}
+
+// Outer scopes first, so the nearest scope setting a key wins
+fn scope_defaults(scopes: &[Vec<Attribute>]) -> Vec<Attribute> {
+    let mut defaults = vec![];
+    for scope in scopes {
+        merge_into(&mut defaults, scope);
+    }
+    defaults
+}
+
+impl Resolver {
+    // Defaults only apply to a node when it is first seen, like graphviz does
+    fn resolve_node(&mut self, node_id: &NodeId, attributes: &[Attribute]) {
+        let node_scopes = &self.node_scopes;
+        let resolved = self
+            .nodes
+            .entry(node_id.id.clone())
+            .or_insert_with(|| scope_defaults(node_scopes));
+        merge_into(resolved, attributes);
+    }
+
+    fn resolve_scope(&mut self, statements: &[Statement]) {
+        self.node_scopes.push(vec![]);
+        self.edge_scopes.push(vec![]);
+        self.resolve_statements(statements);
+        self.node_scopes.pop();
+        self.edge_scopes.pop();
+    }
+
+    // Same order as DotGraph::edges, edges inside a subgraph endpoint come first
+    fn resolve_edge_stmt(&mut self, edge_stmt: &EdgeStmt) {
+        let mut attributes = scope_defaults(&self.edge_scopes);
+        merge_into(
+            &mut attributes,
+            edge_stmt.attributes.as_deref().unwrap_or_default(),
+        );
+        let sides = edge_sides(edge_stmt);
+        for (idx, side) in sides.iter().enumerate() {
+            match side {
+                EdgeStmtSide::NodeId(node_id) => self.resolve_node(node_id, &[]),
+                EdgeStmtSide::SubGraph(subgraph) => self.resolve_scope(&subgraph.statements),
+            }
+            if idx == 0 {
+                continue;
+            }
+            for from in side_node_ids(sides[idx - 1]) {
+                for to in side_node_ids(side) {
+                    self.edges.push(ResolvedEdge {
+                        from: from.clone(),
+                        to: to.clone(),
+                        attributes: attributes.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn resolve_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::AttrStmt(attr_stmt) => {
+                    let scopes = match attr_stmt.kind {
+                        AttrStmtKind::Node => &mut self.node_scopes,
+                        AttrStmtKind::Edge => &mut self.edge_scopes,
+                        AttrStmtKind::Graph => continue,
+                    };
+                    if let Some(scope) = scopes.last_mut() {
+                        merge_into(scope, &attr_stmt.attr_list.items);
+                    }
+                }
+                Statement::NodeStmt(node_stmt) => self.resolve_node(
+                    &node_stmt.id,
+                    node_stmt.attributes.as_deref().unwrap_or_default(),
+                ),
+                Statement::EdgeStmt(edge_stmt) => self.resolve_edge_stmt(edge_stmt),
+                Statement::SubGraph(subgraph) => self.resolve_scope(&subgraph.statements),
+                Statement::AttributeStmt(_) => {}
+            }
+        }
+    }
+}
+
+impl DotGraph {
+    fn resolve_defaults(&self) -> Resolver {
+        let mut resolver = Resolver::default();
+        resolver.resolve_scope(self.statements.as_deref().unwrap_or_default());
+        resolver
+    }
+
+    // Attributes of every node after applying `node [...]` defaults from the enclosing scopes
+    pub fn resolved_node_attrs(&self) -> HashMap<String, Vec<Attribute>> {
+        self.resolve_defaults().nodes
+    }
+
+    // DotGraph::edges with `edge [...]` defaults from the enclosing scopes applied
+    pub fn resolved_edges(&self) -> Vec<ResolvedEdge> {
+        self.resolve_defaults().edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    fn attr(lhs: &str, rhs: &str) -> Attribute {
+        Attribute::new(lhs.to_string(), rhs.to_string())
+    }
+
+    #[test]
+    fn test_nested_subgraph_defaults() {
+        let code = "digraph {
+            node [color=red, shape=box];
+            a;
+            subgraph outer {
+                b;
+                subgraph inner { node [color=blue]; c; d [shape=circle] }
+                e
+            }
+            f -> g
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let nodes = dg.resolved_node_attrs();
+        let red_box = vec![attr("color", "red"), attr("shape", "box")];
+        assert_eq!(nodes["a"], red_box);
+        assert_eq!(nodes["b"], red_box);
+        assert_eq!(
+            nodes["c"],
+            vec![attr("color", "blue"), attr("shape", "box")]
+        );
+        assert_eq!(
+            nodes["d"],
+            vec![attr("color", "blue"), attr("shape", "circle")]
+        );
+        assert_eq!(nodes["e"], red_box);
+        assert_eq!(nodes["f"], red_box);
+        assert_eq!(nodes["g"], red_box);
+    }
+
+    #[test]
+    fn test_defaults_apply_when_node_is_first_seen() {
+        let code = "graph { a; node [color=red]; a [label=x]; b }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let nodes = dg.resolved_node_attrs();
+        assert_eq!(nodes["a"], vec![attr("label", "x")]);
+        assert_eq!(nodes["b"], vec![attr("color", "red")]);
+    }
+
+    #[test]
+    fn test_edge_defaults_apply_to_later_edges() {
+        let code = "digraph {
+            a -> b;
+            edge [color=gray];
+            b -> c -> d [style=dashed];
+            subgraph s { edge [color=blue]; d -> e; e -> f [color=red] }
+            f -> g
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let edges: Vec<(String, String, Vec<Attribute>)> = dg
+            .resolved_edges()
+            .into_iter()
+            .map(|edge| (edge.from.id, edge.to.id, edge.attributes))
+            .collect();
+        let edge = |from: &str, to: &str, attributes: Vec<Attribute>| {
+            (from.to_string(), to.to_string(), attributes)
+        };
+        let gray_dashed = vec![attr("color", "gray"), attr("style", "dashed")];
+        assert_eq!(
+            edges,
+            vec![
+                edge("a", "b", vec![]),
+                edge("b", "c", gray_dashed.clone()),
+                edge("c", "d", gray_dashed),
+                edge("d", "e", vec![attr("color", "blue")]),
+                edge("e", "f", vec![attr("color", "red")]),
+                edge("f", "g", vec![attr("color", "gray")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_statements_accumulate() {
+        let code = "digraph { node [a=1]; x; node [b=2]; y; node [a=3]; z; edge [c=4]; edge [d=5]; x -> y }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let nodes = dg.resolved_node_attrs();
+        assert_eq!(nodes["x"], vec![attr("a", "1")]);
+        assert_eq!(nodes["y"], vec![attr("a", "1"), attr("b", "2")]);
+        assert_eq!(nodes["z"], vec![attr("a", "3"), attr("b", "2")]);
+        assert_eq!(
+            dg.resolved_edges()[0].attributes,
+            vec![attr("c", "4"), attr("d", "5")]
+        );
+    }
+
+    #[test]
+    fn test_later_edge_default_replaces_earlier_one() {
+        let code = "digraph {
+            edge [color=red];
+            a -> b; b -> c [style=bold];
+            edge [color=blue];
+            c -> d; d -> e [color=green]
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let colors: Vec<(String, String)> = dg
+            .resolved_edges()
+            .into_iter()
+            .map(|edge| {
+                let color = edge.attributes.iter().find(|attr| attr.lhs == "color");
+                (edge.to.id, color.unwrap().rhs.clone())
+            })
+            .collect();
+        let color = |to: &str, color: &str| (to.to_string(), color.to_string());
+        assert_eq!(
+            colors,
+            vec![
+                color("b", "red"),
+                color("c", "red"),
+                color("d", "blue"),
+                color("e", "green")
+            ]
+        );
+        assert_eq!(
+            dg.resolved_edges()[1].attributes,
+            vec![attr("color", "red"), attr("style", "bold")]
+        );
+    }
+}
@@ -0,0 +1,935 @@
+use std::{collections::BTreeMap, fmt, io};
+
+use crate::{
+    parser::grammer::{
+        AttrList, AttrStmt, AttrStmtKind, Attribute, AttributeStmt, Compass, DotGraph, EdgeOp,
+        EdgeRhs, EdgeStmt, EdgeStmtSide, GraphType, NodeId, Port, Statement, SubGraph,
+    },
+    visit::{walk_mut, VisitorMut},
+};
+
+const INDENT: &str = "    ";
+
+fn is_keyword(id: &str) -> bool {
+    matches!(
+        id.to_lowercase().as_str(),
+        "node" | "edge" | "graph" | "digraph" | "subgraph" | "strict"
+    )
+}
+
+// Same rules as the tokenizer's alphabetic id: [a-zA-Z\x80-\xFF_][a-zA-Z\x80-\xFF_0-9]*
+fn is_alphabetic_id(id: &str) -> bool {
+    let is_letter =
+        |c: char| c.is_ascii_alphabetic() || c == '_' || ('\u{80}'..='\u{FF}').contains(&c);
+    let mut chars = id.chars();
+    match chars.next() {
+        Some(first) if is_letter(first) => chars.all(|c| is_letter(c) || c.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+// Same rules as the tokenizer's numeral id: [-]?(.[0-9]⁺ | [0-9]⁺(.[0-9]*)? )
+fn is_numeral_id(id: &str) -> bool {
+    let unsigned = id.strip_prefix('-').unwrap_or(id);
+    let (whole, fraction) = match unsigned.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    match fraction {
+        None => !whole.is_empty() && all_digits(whole),
+        Some(fraction) if whole.is_empty() => !fraction.is_empty() && all_digits(fraction),
+        Some(fraction) => all_digits(whole) && all_digits(fraction),
+    }
+}
+
+// Identifiers only get quoted when the tokenizer would not read them back as the same ID
+// With always_quote everything but numerals is quoted
+fn write_id(f: &mut dyn fmt::Write, options: &DisplayOptions, id: &str) -> fmt::Result {
+    let bare = if options.always_quote {
+        is_numeral_id(id)
+    } else {
+        !is_keyword(id) && (is_alphabetic_id(id) || is_numeral_id(id))
+    };
+    if bare {
+        write!(f, "{}", id)
+    } else {
+        write_quoted(f, id)
+    }
+}
+
+fn write_quoted(f: &mut dyn fmt::Write, id: &str) -> fmt::Result {
+    write!(f, "{}", escape_quoted(id, true))
+}
+
+// Keys written quoted in the source stay quoted, others are written like any id
+fn write_key(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    key: &str,
+    quoted: bool,
+) -> fmt::Result {
+    if quoted {
+        write_quoted(f, key)
+    } else {
+        write_id(f, options, key)
+    }
+}
+
+// Turns an arbitrary string into a quoted ID that reads back as the same text
+pub fn escape_dot_string(s: &str) -> String {
+    escape_quoted(s, false)
+}
+
+// Parsed ids keep their escapes as written, so with keep_escapes an escape sequence is
+// copied as is and only a bare '"' or a trailing '\' (which would eat the closing quote)
+// gets escaped
+fn escape_quoted(s: &str, keep_escapes: bool) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' if keep_escapes => match chars.next() {
+                Some(next) => {
+                    escaped.push('\\');
+                    escaped.push(next);
+                }
+                None => escaped.push_str("\\\\"),
+            },
+            '\\' => escaped.push_str("\\\\"),
+            '\n' if !keep_escapes => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl fmt::Display for Compass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compass = match self {
+            Compass::N => "n",
+            Compass::Ne => "ne",
+            Compass::E => "e",
+            Compass::Se => "se",
+            Compass::S => "s",
+            Compass::Sw => "sw",
+            Compass::W => "w",
+            Compass::Nw => "nw",
+            Compass::C => "c",
+            Compass::Underscore => "_",
+        };
+        write!(f, "{}", compass)
+    }
+}
+
+fn write_port(f: &mut dyn fmt::Write, options: &DisplayOptions, port: &Port) -> fmt::Result {
+    if let Some(id) = &port.id {
+        write!(f, ":")?;
+        write_id(f, options, id)?;
+    }
+    if let Some(compass) = &port.compass {
+        write!(f, ":{}", compass)?;
+    }
+    Ok(())
+}
+
+fn write_node_id(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    node_id: &NodeId,
+) -> fmt::Result {
+    write_id(f, options, &node_id.id)?;
+    if let Some(port) = &node_id.port {
+        write_port(f, options, port)?;
+    }
+    Ok(())
+}
+
+fn write_attribute(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    attribute: &Attribute,
+) -> fmt::Result {
+    write_key(f, options, &attribute.lhs, attribute.quoted_lhs)?;
+    write!(f, "=")?;
+    write_id(f, options, &attribute.rhs)
+}
+
+impl fmt::Display for Port {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_port(f, &DisplayOptions::default(), self)
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_node_id(f, &DisplayOptions::default(), self)
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_attribute(f, &DisplayOptions::default(), self)
+    }
+}
+
+impl fmt::Display for EdgeOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeOp::Directed => write!(f, "->"),
+            EdgeOp::UnDirected => write!(f, "--"),
+        }
+    }
+}
+
+fn write_attr_list(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    attributes: &[Attribute],
+) -> fmt::Result {
+    write!(f, "[")?;
+    for (idx, attribute) in attributes.iter().enumerate() {
+        if idx > 0 {
+            write!(f, ", ")?;
+        }
+        write_attribute(f, options, attribute)?;
+    }
+    write!(f, "]")
+}
+
+fn write_subgraph_head(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    subgraph: &SubGraph,
+) -> fmt::Result {
+    if let Some(id) = &subgraph.id {
+        write!(f, "subgraph ")?;
+        write_id(f, options, id)?;
+        write!(f, " ")?;
+    }
+    Ok(())
+}
+
+// Subgraphs used as edge endpoints are kept on one line
+fn write_edge_side(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    side: &EdgeStmtSide,
+) -> fmt::Result {
+    match side {
+        EdgeStmtSide::NodeId(node_id) => write_node_id(f, options, node_id),
+        EdgeStmtSide::SubGraph(subgraph) => {
+            write_subgraph_head(f, options, subgraph)?;
+            write!(f, "{{")?;
+            for (idx, statement) in subgraph.statements.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, "; ")?;
+                }
+                write_statement(f, options, statement, 0)?;
+            }
+            write!(f, "}}")
+        }
+    }
+}
+
+fn write_edge_stmt(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    edge_stmt: &EdgeStmt,
+) -> fmt::Result {
+    write_edge_side(f, options, &edge_stmt.edge_lhs)?;
+    let mut rhs: Option<&EdgeRhs> = Some(&edge_stmt.edge_rhs);
+    while let Some(current) = rhs {
+        write!(f, " {} ", current.edge_op)?;
+        write_edge_side(f, options, &current.edge_to)?;
+        rhs = current.edge_optional.as_deref();
+    }
+    if let Some(attributes) = &edge_stmt.attributes {
+        write!(f, " ")?;
+        write_attr_list(f, options, attributes)?;
+    }
+    Ok(())
+}
+
+fn write_statements(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    statements: &[Statement],
+    depth: usize,
+) -> fmt::Result {
+    if statements.is_empty() {
+        return write!(f, "{{}}");
+    }
+    writeln!(f, "{{")?;
+    write_statement_lines(f, options, statements, depth + 1)?;
+    write!(f, "{}}}", INDENT.repeat(depth))
+}
+
+// One statement per line, indented to `depth`
+fn write_statement_lines(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    statements: &[Statement],
+    depth: usize,
+) -> fmt::Result {
+    for statement in statements {
+        if let (Some(ranks), Statement::NodeStmt(node_stmt)) = (&options.annotate_ranks, statement)
+        {
+            if let Some(rank) = ranks.get(&node_stmt.id.id) {
+                writeln!(f, "{}// rank {}", INDENT.repeat(depth), rank)?;
+            }
+        }
+        write!(f, "{}", INDENT.repeat(depth))?;
+        write_statement(f, options, statement, depth)?;
+        // subgraph blocks close with a brace, everything else gets a ;
+        if !matches!(statement, Statement::SubGraph(_)) {
+            write!(f, ";")?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+fn write_statement(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    statement: &Statement,
+    depth: usize,
+) -> fmt::Result {
+    match statement {
+        Statement::NodeStmt(node_stmt) => {
+            write_node_id(f, options, &node_stmt.id)?;
+            if let Some(attributes) = &node_stmt.attributes {
+                write!(f, " ")?;
+                write_attr_list(f, options, attributes)?;
+            }
+            Ok(())
+        }
+        Statement::EdgeStmt(edge_stmt) => write_edge_stmt(f, options, edge_stmt),
+        Statement::AttrStmt(attr_stmt) => {
+            let kind = match attr_stmt.kind {
+                AttrStmtKind::Graph => "graph",
+                AttrStmtKind::Node => "node",
+                AttrStmtKind::Edge => "edge",
+            };
+            write!(f, "{} ", kind)?;
+            write_attr_list(f, options, &attr_stmt.attr_list.items)
+        }
+        Statement::AttributeStmt(attribute_stmt) => {
+            write_key(f, options, &attribute_stmt.lhs, attribute_stmt.quoted_lhs)?;
+            write!(f, "=")?;
+            write_id(f, options, &attribute_stmt.rhs)
+        }
+        Statement::SubGraph(subgraph) => {
+            write_subgraph_head(f, options, subgraph)?;
+            write_statements(f, options, &subgraph.statements, depth)
+        }
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_statement(f, &DisplayOptions::default(), self, 0)
+    }
+}
+
+impl fmt::Display for SubGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = DisplayOptions::default();
+        write_subgraph_head(f, &options, self)?;
+        write_statements(f, &options, &self.statements, 0)
+    }
+}
+
+impl SubGraph {
+    // The statements inside the braces, one per line, for embedding into another graph
+    pub fn statements_to_string(&self) -> String {
+        let mut out = String::new();
+        // writing into a String can not fail
+        let _ = write_statement_lines(&mut out, &DisplayOptions::default(), &self.statements, 0);
+        out
+    }
+}
+
+fn write_graph(f: &mut dyn fmt::Write, options: &DisplayOptions, dg: &DotGraph) -> fmt::Result {
+    write_graph_head(f, options, dg)?;
+    write_statements(f, options, dg.statements.as_deref().unwrap_or(&[]), 0)
+}
+
+fn write_graph_head(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions,
+    dg: &DotGraph,
+) -> fmt::Result {
+    if dg.strict_mode {
+        write!(f, "strict ")?;
+    }
+    match dg.graph_type {
+        Some(GraphType::Digraph) => write!(f, "digraph ")?,
+        _ => write!(f, "graph ")?,
+    }
+    if let Some(id) = &dg.id {
+        write_id(f, options, id)?;
+        write!(f, " ")?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for DotGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_graph(f, &DisplayOptions::default(), self)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DisplayOptions {
+    // Attributes every edge statement has are written once as a top level `edge [...]`
+    pub factor_edge_attributes: bool,
+    // Quote every id except numerals, even when it would read back the same unquoted
+    pub always_quote: bool,
+    // Give anonymous subgraphs generated ids `_anon_0`, `_anon_1`, ... in document order
+    pub name_anonymous: bool,
+    // Graphviz engine to lay the graph out with (dot, neato, fdp, ...), written as a leading
+    // `layout=<engine>` statement in place of any top level layout the graph already sets
+    pub layout_engine: Option<String>,
+    // Ranks from DotGraph::ranks, written as a `// rank N` line above each node statement
+    // Nodes missing from the map get no comment
+    pub annotate_ranks: Option<BTreeMap<String, usize>>,
+}
+
+fn set_layout(statements: &mut Vec<Statement>, engine: &str) {
+    statements.retain_mut(|statement| match statement {
+        Statement::AttributeStmt(attribute_stmt) => attribute_stmt.lhs != "layout",
+        Statement::AttrStmt(attr_stmt) if attr_stmt.kind == AttrStmtKind::Graph => {
+            attr_stmt
+                .attr_list
+                .items
+                .retain(|attribute| attribute.lhs != "layout");
+            !attr_stmt.attr_list.items.is_empty()
+        }
+        _ => true,
+    });
+    statements.insert(
+        0,
+        Statement::AttributeStmt(AttributeStmt {
+            lhs: "layout".to_string(),
+            rhs: engine.to_string(),
+            quoted_lhs: false,
+        }),
+    );
+}
+
+struct NameAnonymous {
+    next: usize,
+}
+
+impl VisitorMut for NameAnonymous {
+    fn visit_subgraph(&mut self, subgraph: &mut SubGraph) {
+        if subgraph.id.is_none() {
+            subgraph.id = Some(format!("_anon_{}", self.next));
+            self.next += 1;
+        }
+    }
+}
+
+// Visits every edge statement, including ones inside subgraphs and subgraph endpoints
+// Returns false as soon as `visit` does
+fn visit_edge_stmts(
+    statements: &mut [Statement],
+    visit: &mut dyn FnMut(&mut Statement) -> bool,
+) -> bool {
+    for statement in statements {
+        if !visit(statement) {
+            return false;
+        }
+        let keep_going = match statement {
+            Statement::EdgeStmt(edge_stmt) => {
+                let mut sides = vec![&mut edge_stmt.edge_lhs];
+                let mut rhs = Some(&mut edge_stmt.edge_rhs);
+                while let Some(current) = rhs {
+                    sides.push(&mut current.edge_to);
+                    rhs = current.edge_optional.as_deref_mut();
+                }
+                sides.into_iter().all(|side| match side {
+                    EdgeStmtSide::SubGraph(subgraph) => {
+                        visit_edge_stmts(&mut subgraph.statements, visit)
+                    }
+                    EdgeStmtSide::NodeId(_) => true,
+                })
+            }
+            Statement::SubGraph(subgraph) => visit_edge_stmts(&mut subgraph.statements, visit),
+            _ => true,
+        };
+        if !keep_going {
+            return false;
+        }
+    }
+    true
+}
+
+// Moving attributes to an `edge [...]` default only keeps the meaning when every edge has them
+// and no other edge defaults exist that they could interact with
+fn factor_edge_attributes(statements: &mut Vec<Statement>) {
+    let mut shared: Option<Vec<Attribute>> = None;
+    let mut edge_stmts = 0;
+    let factorable = visit_edge_stmts(statements, &mut |statement| match statement {
+        Statement::AttrStmt(attr_stmt) => attr_stmt.kind != AttrStmtKind::Edge,
+        Statement::EdgeStmt(edge_stmt) => {
+            let attributes = edge_stmt.attributes.as_deref().unwrap_or_default();
+            edge_stmts += 1;
+            match &mut shared {
+                None => shared = Some(attributes.to_vec()),
+                Some(shared) => shared.retain(|attribute| attributes.contains(attribute)),
+            }
+            true
+        }
+        _ => true,
+    });
+    let Some(shared) = shared.filter(|shared| factorable && edge_stmts > 1 && !shared.is_empty())
+    else {
+        return;
+    };
+
+    visit_edge_stmts(statements, &mut |statement| {
+        if let Statement::EdgeStmt(edge_stmt) = statement {
+            if let Some(attributes) = &mut edge_stmt.attributes {
+                attributes.retain(|attribute| !shared.contains(attribute));
+                if attributes.is_empty() {
+                    edge_stmt.attributes = None;
+                }
+            }
+        }
+        true
+    });
+    statements.insert(
+        0,
+        Statement::AttrStmt(AttrStmt::new(
+            AttrStmtKind::Edge,
+            AttrList { items: shared },
+        )),
+    );
+}
+
+impl DotGraph {
+    pub fn to_string_with_options(&self, options: &DisplayOptions) -> String {
+        let mut out = String::new();
+        // writing into a String can not fail
+        let _ = self.write_with_options(&mut out, options);
+        out
+    }
+
+    fn write_with_options(&self, f: &mut dyn fmt::Write, options: &DisplayOptions) -> fmt::Result {
+        if !options.factor_edge_attributes
+            && !options.name_anonymous
+            && options.layout_engine.is_none()
+        {
+            return write_graph(f, options, self);
+        }
+        let mut dg = self.clone();
+        if options.name_anonymous {
+            walk_mut(&mut dg, &mut NameAnonymous { next: 0 });
+        }
+        if let Some(engine) = &options.layout_engine {
+            set_layout(dg.statements.get_or_insert_with(Vec::new), engine);
+        }
+        if options.factor_edge_attributes {
+            if let Some(statements) = &mut dg.statements {
+                factor_edge_attributes(statements);
+            }
+        }
+        write_graph(f, options, &dg)
+    }
+
+    // The whole graph with the top level statement at `index` written as `new_src` verbatim
+    pub(crate) fn to_string_replacing_statement(&self, index: usize, new_src: &str) -> String {
+        let options = DisplayOptions::default();
+        let statements = self.statements.as_deref().unwrap_or(&[]);
+        let mut out = String::new();
+        // writing into a String can not fail
+        let _ = write_graph_head(&mut out, &options, self);
+        out.push_str("{\n");
+        let _ = write_statement_lines(&mut out, &options, &statements[..index], 1);
+        out.push_str(new_src);
+        out.push('\n');
+        let _ = write_statement_lines(&mut out, &options, &statements[index + 1..], 1);
+        out.push('}');
+        out
+    }
+}
+
+// Lets the fmt based writers above stream into an io::Write, keeping the io error around
+struct IoAdapter<'a, W: io::Write> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+impl DotGraph {
+    // Same output as Display, written straight to `w` without building a String first
+    pub fn write_dot<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_dot_with_options(w, &DisplayOptions::default())
+    }
+
+    pub fn write_dot_with_options<W: io::Write>(
+        &self,
+        w: &mut W,
+        options: &DisplayOptions,
+    ) -> io::Result<()> {
+        let mut adapter = IoAdapter {
+            inner: w,
+            error: None,
+        };
+        self.write_with_options(&mut adapter, options).map_err(|_| {
+            adapter
+                .error
+                .take()
+                .unwrap_or_else(|| io::Error::other("Could not format graph"))
+        })
+    }
+
+    // One `from<TAB>to` line per flattened edge, in document order
+    // Undirected edges are written with sorted endpoints
+    pub fn to_edge_list(&self) -> String {
+        let mut out = String::new();
+        for edge in self.edges() {
+            let (from, to) = self.edge_key(&edge.from.id, &edge.to.id);
+            out.push_str(&format!("{}\t{}\n", from, to));
+        }
+        out
+    }
+
+    // to_edge_list behind a `# from<TAB>to` comment line naming the columns
+    pub fn to_edge_list_with_header(&self) -> String {
+        format!("# from\tto\n{}", self.to_edge_list())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    #[test]
+    fn test_display_empty_graphs() {
+        assert_eq!(DotGraph::new(GraphType::Digraph).to_string(), "digraph {}");
+        assert_eq!(DotGraph::default().to_string(), "graph {}");
+    }
+
+    #[test]
+    fn test_display_statements() {
+        let code = "strict digraph G {
+    rankdir=LR;
+    node [shape=box];
+    a:p1:n -> b -> {c; d} [color=red, label=\"an edge\"];
+    subgraph cluster_0 {
+        e;
+    }
+}";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.to_string(), code);
+    }
+
+    #[test]
+    fn test_display_quotes_ids_when_needed() {
+        assert_eq!(
+            Attribute::new("label".to_string(), "node".to_string()).to_string(),
+            "label=\"node\""
+        );
+        assert_eq!(
+            Attribute::new("width".to_string(), "-1.5".to_string()).to_string(),
+            "width=-1.5"
+        );
+        assert_eq!(
+            Attribute::new("a b".to_string(), "1x".to_string()).to_string(),
+            "\"a b\"=\"1x\""
+        );
+    }
+
+    #[test]
+    fn test_display_quoted_attribute_keys_round_trip() {
+        let code = "graph {
+    \"rankdir\"=LR;
+    a [\"a b\"=1, \"color\"=red, shape=\"box\"];
+}";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        // keys keep the quotes they were written with, values are only quoted where needed
+        let printed = dg.to_string();
+        assert_eq!(
+            printed,
+            "graph {
+    \"rankdir\"=LR;
+    a [\"a b\"=1, \"color\"=red, shape=box];
+}"
+        );
+        assert_eq!(parse(&tokenize(printed).unwrap()).unwrap(), dg);
+    }
+
+    #[test]
+    fn test_write_dot_matches_display() {
+        let code = "digraph G { rankdir=LR; a -> {b c} [label=\"x y\"]; subgraph s { d } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let mut out: Vec<u8> = vec![];
+        dg.write_dot(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), dg.to_string());
+    }
+
+    #[test]
+    fn test_display_factor_edge_attributes() {
+        let code = "digraph { a -> b [color=red]; b -> c [color=red, label=x]; subgraph s { c -> d [color=red] } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let options = DisplayOptions {
+            factor_edge_attributes: true,
+            ..Default::default()
+        };
+        let printed = dg.to_string_with_options(&options);
+        assert_eq!(
+            printed,
+            "digraph {
+    edge [color=red];
+    a -> b;
+    b -> c [label=x];
+    subgraph s {
+        c -> d;
+    }
+}"
+        );
+        let mut out: Vec<u8> = vec![];
+        dg.write_dot_with_options(&mut out, &options).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), printed);
+        assert_eq!(
+            parse(&tokenize(printed).unwrap()).unwrap().resolved_edges(),
+            dg.resolved_edges()
+        );
+    }
+
+    #[test]
+    fn test_display_factor_edge_attributes_only_when_shared_by_all() {
+        let options = DisplayOptions {
+            factor_edge_attributes: true,
+            ..Default::default()
+        };
+        for code in [
+            "digraph { a -> b [color=red]; b -> c }",
+            "digraph { a -> b [color=red]; edge [style=bold]; b -> c [color=red] }",
+            "digraph { a -> b [color=red] }",
+        ] {
+            let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+            assert_eq!(dg.to_string_with_options(&options), dg.to_string());
+        }
+    }
+
+    #[test]
+    fn test_display_always_quote() {
+        let code = "digraph G { a:p1 -> b [weight=2, label=x]; c [width=-1.5] }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let options = DisplayOptions {
+            always_quote: true,
+            ..Default::default()
+        };
+        let printed = dg.to_string_with_options(&options);
+        assert_eq!(
+            printed,
+            "digraph \"G\" {
+    \"a\":\"p1\" -> \"b\" [\"weight\"=2, \"label\"=\"x\"];
+    \"c\" [\"width\"=-1.5];
+}"
+        );
+        // the keys now read back as quoted, so compare the reprint rather than the graphs
+        let reparsed = parse(&tokenize(printed.clone()).unwrap()).unwrap();
+        assert_eq!(reparsed.to_string_with_options(&options), printed);
+    }
+
+    #[test]
+    fn test_escape_dot_string() {
+        assert_eq!(escape_dot_string("plain"), "\"plain\"");
+        assert_eq!(escape_dot_string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(escape_dot_string("C:\\temp"), "\"C:\\\\temp\"");
+        assert_eq!(escape_dot_string("one\ntwo"), "\"one\\ntwo\"");
+        assert_eq!(escape_dot_string(""), "\"\"");
+    }
+
+    #[test]
+    fn test_escape_dot_string_reads_back_as_one_id() {
+        let label = escape_dot_string("a \"quoted\" \\ path\nnext line");
+        let code = format!("graph {{ a [label={}] }}", label);
+        let dg = parse(&tokenize(code).unwrap()).unwrap();
+        assert_eq!(
+            dg.to_string(),
+            format!("graph {{\n    a [label={}];\n}}", label)
+        );
+    }
+
+    #[test]
+    fn test_display_escapes_quotes_in_ids() {
+        // ids built outside the parser can hold a bare quote or end in a backslash
+        let attribute = Attribute::new("say \"hi\"".to_string(), "C:\\temp\\".to_string());
+        let printed = attribute.to_string();
+        assert_eq!(printed, "\"say \\\"hi\\\"\"=\"C:\\temp\\\\\"");
+        let code = format!("graph {{ a [{}] }}", printed);
+        let dg = parse(&tokenize(code).unwrap()).unwrap();
+        assert_eq!(
+            dg.to_string(),
+            format!("graph {{\n    a [{}];\n}}", printed)
+        );
+    }
+
+    #[test]
+    fn test_subgraph_statements_to_string() {
+        let code = "graph { subgraph cluster_a { label=A; a -- b; subgraph inner { c } } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let Some(Statement::SubGraph(subgraph)) = dg.statements.as_ref().unwrap().first() else {
+            panic!("expected a subgraph");
+        };
+        let inner = subgraph.statements_to_string();
+        assert_eq!(inner, "label=A;\na -- b;\nsubgraph inner {\n    c;\n}\n");
+
+        let indented: String = inner
+            .lines()
+            .map(|line| format!("    {}\n", line))
+            .collect();
+        assert_eq!(
+            subgraph.to_string(),
+            format!("subgraph cluster_a {{\n{}}}", indented)
+        );
+        assert_eq!(SubGraph::default().statements_to_string(), "");
+    }
+
+    #[test]
+    fn test_to_edge_list() {
+        let code = "graph { c -- a -- b; {d e} -- c; b -- a }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.to_edge_list(), "a\tc\na\tb\nc\td\nc\te\na\tb\n");
+
+        let dg = parse(&tokenize("digraph { b -> a }".to_string()).unwrap()).unwrap();
+        assert_eq!(dg.to_edge_list_with_header(), "# from\tto\nb\ta\n");
+        assert_eq!(DotGraph::default().to_edge_list(), "");
+    }
+
+    #[test]
+    fn test_display_name_anonymous_subgraphs() {
+        let code = "graph { {a b}; subgraph named { {c} } d -- {e} }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let options = DisplayOptions {
+            name_anonymous: true,
+            ..DisplayOptions::default()
+        };
+        assert_eq!(
+            dg.to_string_with_options(&options),
+            "graph {
+    subgraph _anon_0 {
+        a;
+        b;
+    }
+    subgraph named {
+        subgraph _anon_1 {
+            c;
+        }
+    }
+    d -- subgraph _anon_2 {e};
+}"
+        );
+        assert!(dg.to_string().contains("d -- {e}"));
+    }
+
+    #[test]
+    fn test_display_layout_engine() {
+        let code = "graph { layout=dot; graph [layout=fdp, splines=true]; a -- b }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let options = DisplayOptions {
+            layout_engine: Some("neato".to_string()),
+            ..DisplayOptions::default()
+        };
+        let printed = dg.to_string_with_options(&options);
+        assert_eq!(
+            printed,
+            "graph {
+    layout=neato;
+    graph [splines=true];
+    a -- b;
+}"
+        );
+        let reparsed = parse(&tokenize(printed).unwrap()).unwrap();
+        assert_eq!(reparsed.graph_attrs()["layout"], "neato");
+
+        assert_eq!(
+            DotGraph::default().to_string_with_options(&options),
+            "graph {\n    layout=neato;\n}"
+        );
+    }
+
+    #[test]
+    fn test_display_keeps_attribute_statement_position() {
+        let code = "digraph { a; rankdir=LR; b [shape=box]; subgraph s { c; label=S; d } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let statements = dg.statements.as_ref().unwrap();
+        assert!(statements[0].is_node());
+        assert!(matches!(
+            statements[1].as_attribute_stmt(),
+            Some(attribute_stmt) if attribute_stmt.lhs == "rankdir"
+        ));
+        assert!(statements[2].is_node());
+        let printed = dg.to_string();
+        assert_eq!(
+            printed,
+            "digraph {
+    a;
+    rankdir=LR;
+    b [shape=box];
+    subgraph s {
+        c;
+        label=S;
+        d;
+    }
+}"
+        );
+        assert_eq!(parse(&tokenize(printed).unwrap()).unwrap(), dg);
+        crate::testing::assert_roundtrip(code);
+    }
+
+    #[test]
+    fn test_display_annotate_ranks() {
+        let code = "digraph { a; b [shape=box]; a -> b -> c; subgraph s { c; d } x -> y }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let ranks = dg.ranks().unwrap();
+        let options = DisplayOptions {
+            annotate_ranks: Some(ranks.clone()),
+            ..DisplayOptions::default()
+        };
+        let printed = dg.to_string_with_options(&options);
+        assert_eq!(
+            printed,
+            "digraph {
+    // rank 0
+    a;
+    // rank 1
+    b [shape=box];
+    a -> b -> c;
+    subgraph s {
+        // rank 2
+        c;
+        // rank 0
+        d;
+    }
+    x -> y;
+}"
+        );
+        let expected: Vec<(&str, usize)> = vec![("a", 0), ("b", 1), ("c", 2), ("d", 0)];
+        for (id, rank) in expected {
+            assert_eq!(ranks[id], rank);
+        }
+        // the comments do not change the graph
+        assert_eq!(parse(&tokenize(printed).unwrap()).unwrap(), dg);
+    }
+}
@@ -0,0 +1,1122 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    lint::{Lint, LintKind},
+    parser::grammer::{
+        AttrStmtKind, Attribute, DotGraph, EdgeOp, EdgeRhs, EdgeStmt, EdgeStmtSide, GraphType,
+        NodeId, ParserError, Port, Statement, SubGraph,
+    },
+    tokenizer::{Delimiter, Token},
+};
+
+// A single flattened edge
+// `a -> b -> c` gives two edges, `{a b} -> c` gives one edge per node in the subgraph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge<'a> {
+    pub from: &'a NodeId,
+    pub to: &'a NodeId,
+    pub attributes: &'a [Attribute],
+}
+
+impl SubGraph {
+    // Every node mentioned inside the subgraph (and nested subgraphs), first mention wins
+    pub fn node_ids(&self) -> Vec<&NodeId> {
+        let mut ids = OrderedNodeIds::default();
+        collect_node_ids(&self.statements, &mut ids);
+        ids.ids
+    }
+
+    // Graphviz draws a subgraph as a cluster when its id starts with "cluster", in any case
+    // Quoted ids are compared without their quotes, so "cluster x" counts too
+    pub fn is_cluster(&self) -> bool {
+        self.id.as_deref().is_some_and(|id| {
+            id.get(..7)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("cluster"))
+        })
+    }
+}
+
+// Ids in first-mention order, with a set of the ids seen so far to skip repeats
+#[derive(Default)]
+struct OrderedNodeIds<'a> {
+    ids: Vec<&'a NodeId>,
+    seen: HashSet<&'a str>,
+}
+
+impl<'a> OrderedNodeIds<'a> {
+    fn push(&mut self, node_id: &'a NodeId) {
+        if self.seen.insert(&node_id.id) {
+            self.ids.push(node_id);
+        }
+    }
+}
+
+fn collect_side_node_ids<'a>(side: &'a EdgeStmtSide, ids: &mut OrderedNodeIds<'a>) {
+    match side {
+        EdgeStmtSide::NodeId(node_id) => ids.push(node_id),
+        EdgeStmtSide::SubGraph(subgraph) => collect_node_ids(&subgraph.statements, ids),
+    }
+}
+
+fn collect_node_ids<'a>(statements: &'a [Statement], ids: &mut OrderedNodeIds<'a>) {
+    for statement in statements {
+        match statement {
+            Statement::NodeStmt(node_stmt) => ids.push(&node_stmt.id),
+            Statement::EdgeStmt(edge_stmt) => {
+                for side in edge_sides(edge_stmt) {
+                    collect_side_node_ids(side, ids);
+                }
+            }
+            Statement::SubGraph(subgraph) => collect_node_ids(&subgraph.statements, ids),
+            Statement::AttrStmt(_) | Statement::AttributeStmt(_) => {}
+        }
+    }
+}
+
+// Stops at the first mention instead of collecting every id like collect_node_ids
+fn mentions_node(statements: &[Statement], id: &str) -> bool {
+    statements.iter().any(|statement| match statement {
+        Statement::NodeStmt(node_stmt) => node_stmt.id.id == id,
+        Statement::EdgeStmt(edge_stmt) => {
+            edge_sides(edge_stmt).into_iter().any(|side| match side {
+                EdgeStmtSide::NodeId(node_id) => node_id.id == id,
+                EdgeStmtSide::SubGraph(subgraph) => mentions_node(&subgraph.statements, id),
+            })
+        }
+        Statement::SubGraph(subgraph) => mentions_node(&subgraph.statements, id),
+        Statement::AttrStmt(_) | Statement::AttributeStmt(_) => false,
+    })
+}
+
+// Like collect_node_ids, but only ids with a node statement, `{b}` in `a -> {b}` included
+fn collect_declared_node_ids<'a>(statements: &'a [Statement], ids: &mut OrderedNodeIds<'a>) {
+    for statement in statements {
+        match statement {
+            Statement::NodeStmt(node_stmt) => ids.push(&node_stmt.id),
+            Statement::EdgeStmt(edge_stmt) => {
+                for side in edge_sides(edge_stmt) {
+                    if let EdgeStmtSide::SubGraph(subgraph) = side {
+                        collect_declared_node_ids(&subgraph.statements, ids);
+                    }
+                }
+            }
+            Statement::SubGraph(subgraph) => collect_declared_node_ids(&subgraph.statements, ids),
+            Statement::AttrStmt(_) | Statement::AttributeStmt(_) => {}
+        }
+    }
+}
+
+// lhs followed by every edge_to in the rhs chain
+pub(crate) fn edge_sides(edge_stmt: &EdgeStmt) -> Vec<&EdgeStmtSide> {
+    let mut sides = vec![&edge_stmt.edge_lhs];
+    let mut rhs: Option<&EdgeRhs> = Some(&edge_stmt.edge_rhs);
+    while let Some(current) = rhs {
+        sides.push(&current.edge_to);
+        rhs = current.edge_optional.as_deref();
+    }
+    sides
+}
+
+pub(crate) fn side_node_ids(side: &EdgeStmtSide) -> Vec<&NodeId> {
+    let mut ids = OrderedNodeIds::default();
+    collect_side_node_ids(side, &mut ids);
+    ids.ids
+}
+
+pub(crate) fn collect_edges<'a>(statements: &'a [Statement], edges: &mut Vec<Edge<'a>>) {
+    for statement in statements {
+        match statement {
+            Statement::EdgeStmt(edge_stmt) => {
+                let attributes = edge_stmt.attributes.as_deref().unwrap_or(&[]);
+                let sides = edge_sides(edge_stmt);
+                for (idx, side) in sides.iter().enumerate() {
+                    // edges declared inside a subgraph endpoint come first
+                    if let EdgeStmtSide::SubGraph(subgraph) = side {
+                        collect_edges(&subgraph.statements, edges);
+                    }
+                    if idx == 0 {
+                        continue;
+                    }
+                    for from in side_node_ids(sides[idx - 1]) {
+                        for to in side_node_ids(side) {
+                            edges.push(Edge {
+                                from,
+                                to,
+                                attributes,
+                            });
+                        }
+                    }
+                }
+            }
+            Statement::SubGraph(subgraph) => collect_edges(&subgraph.statements, edges),
+            Statement::NodeStmt(_) | Statement::AttrStmt(_) | Statement::AttributeStmt(_) => {}
+        }
+    }
+}
+
+// Innermost enclosing subgraph passing `counts` of every node, None for nodes outside of any
+// The first mention inside such a subgraph wins
+fn collect_memberships<'a>(
+    statements: &'a [Statement],
+    enclosing: Option<&'a str>,
+    counts: fn(&SubGraph) -> bool,
+    memberships: &mut HashMap<&'a str, Option<&'a str>>,
+) {
+    let walk_subgraph =
+        |subgraph: &'a SubGraph, memberships: &mut HashMap<&'a str, Option<&'a str>>| {
+            let inner = if counts(subgraph) {
+                subgraph.id.as_deref()
+            } else {
+                enclosing
+            };
+            collect_memberships(&subgraph.statements, inner, counts, memberships);
+        };
+    let add_node = |id: &'a str, memberships: &mut HashMap<&'a str, Option<&'a str>>| {
+        let membership = memberships.entry(id).or_insert(None);
+        if membership.is_none() {
+            *membership = enclosing;
+        }
+    };
+    for statement in statements {
+        match statement {
+            Statement::NodeStmt(node_stmt) => add_node(&node_stmt.id.id, memberships),
+            Statement::EdgeStmt(edge_stmt) => {
+                for side in edge_sides(edge_stmt) {
+                    match side {
+                        EdgeStmtSide::NodeId(node_id) => add_node(&node_id.id, memberships),
+                        EdgeStmtSide::SubGraph(subgraph) => walk_subgraph(subgraph, memberships),
+                    }
+                }
+            }
+            Statement::SubGraph(subgraph) => walk_subgraph(subgraph, memberships),
+            Statement::AttrStmt(_) | Statement::AttributeStmt(_) => {}
+        }
+    }
+}
+
+// Where an attribute from DotGraph::all_attributes was set
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttrScope {
+    Graph,
+    Node(String),
+    Edge(String, String),
+}
+
+fn collect_attributes(statements: &[Statement], attributes: &mut Vec<(AttrScope, String, String)>) {
+    for statement in statements {
+        match statement {
+            Statement::AttrStmt(attr_stmt) if attr_stmt.kind == AttrStmtKind::Graph => {
+                for attribute in &attr_stmt.attr_list.items {
+                    attributes.push((
+                        AttrScope::Graph,
+                        attribute.lhs.clone(),
+                        attribute.rhs.clone(),
+                    ));
+                }
+            }
+            Statement::AttributeStmt(attribute_stmt) => attributes.push((
+                AttrScope::Graph,
+                attribute_stmt.lhs.clone(),
+                attribute_stmt.rhs.clone(),
+            )),
+            Statement::NodeStmt(node_stmt) => {
+                for attribute in node_stmt.attributes.iter().flatten() {
+                    attributes.push((
+                        AttrScope::Node(node_stmt.id.id.clone()),
+                        attribute.lhs.clone(),
+                        attribute.rhs.clone(),
+                    ));
+                }
+            }
+            Statement::EdgeStmt(edge_stmt) => {
+                let sides = edge_sides(edge_stmt);
+                for (idx, side) in sides.iter().enumerate() {
+                    if let EdgeStmtSide::SubGraph(subgraph) = side {
+                        collect_attributes(&subgraph.statements, attributes);
+                    }
+                    if idx == 0 {
+                        continue;
+                    }
+                    for from in side_node_ids(sides[idx - 1]) {
+                        for to in side_node_ids(side) {
+                            for attribute in edge_stmt.attributes.iter().flatten() {
+                                attributes.push((
+                                    AttrScope::Edge(from.id.clone(), to.id.clone()),
+                                    attribute.lhs.clone(),
+                                    attribute.rhs.clone(),
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            Statement::SubGraph(subgraph) => collect_attributes(&subgraph.statements, attributes),
+            Statement::AttrStmt(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RankDir {
+    #[default]
+    TB,
+    LR,
+    BT,
+    RL,
+}
+
+impl RankDir {
+    pub fn from_value(value: &str) -> Option<RankDir> {
+        match value.to_uppercase().as_str() {
+            "TB" => Some(RankDir::TB),
+            "LR" => Some(RankDir::LR),
+            "BT" => Some(RankDir::BT),
+            "RL" => Some(RankDir::RL),
+            _ => None,
+        }
+    }
+}
+
+// Name used for graphs declared without an id
+const DEFAULT_GRAPH_ID: &str = "G";
+
+impl DotGraph {
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    // The declared id, or "G" for anonymous graphs
+    pub fn id_or_default(&self) -> &str {
+        self.id().unwrap_or(DEFAULT_GRAPH_ID)
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.graph_type == Some(GraphType::Digraph)
+    }
+
+    // Endpoints of an edge as a map key; undirected edges are stored with sorted endpoints
+    pub(crate) fn edge_key(&self, from: &str, to: &str) -> (String, String) {
+        if !self.is_directed() && to < from {
+            return (to.to_string(), from.to_string());
+        }
+        (from.to_string(), to.to_string())
+    }
+
+    // All edges in document order, with edge chains and subgraph endpoints expanded
+    pub fn edges(&self) -> Vec<Edge<'_>> {
+        let mut edges = vec![];
+        if let Some(statements) = &self.statements {
+            collect_edges(statements, &mut edges);
+        }
+        edges
+    }
+
+    // Edges with a global, document ordered index
+    pub fn enumerate_edges(&self) -> impl Iterator<Item = (usize, Edge<'_>)> {
+        self.edges().into_iter().enumerate()
+    }
+
+    // Number of edges between each pair of nodes, values above 1 are parallel edges
+    pub fn parallel_edge_counts(&self) -> HashMap<(String, String), usize> {
+        let mut counts: HashMap<(String, String), usize> = HashMap::new();
+        for edge in self.edges() {
+            *counts
+                .entry(self.edge_key(&edge.from.id, &edge.to.id))
+                .or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // Flattened edges sorted by endpoints, undirected edges have their endpoints sorted too
+    pub fn sorted_edges(&self) -> Vec<(String, String)> {
+        let mut edges: Vec<(String, String)> = self
+            .edges()
+            .iter()
+            .map(|edge| self.edge_key(&edge.from.id, &edge.to.id))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    // Every node in the graph (and subgraphs), first mention wins
+    // Nodes only used by edges are included, declared_node_ids leaves them out
+    pub fn node_ids(&self) -> Vec<&NodeId> {
+        let mut ids = OrderedNodeIds::default();
+        collect_node_ids(self.statements.as_deref().unwrap_or_default(), &mut ids);
+        ids.ids
+    }
+
+    // Nodes with a node statement somewhere in the graph, in the order of their first one
+    pub fn declared_node_ids(&self) -> Vec<&NodeId> {
+        let mut ids = OrderedNodeIds::default();
+        collect_declared_node_ids(self.statements.as_deref().unwrap_or_default(), &mut ids);
+        ids.ids
+    }
+
+    // Owned ids in first-mention order, stable indices for matrices and layouts
+    pub fn node_ids_in_order(&self) -> Vec<String> {
+        self.node_ids()
+            .into_iter()
+            .map(|node_id| node_id.id.clone())
+            .collect()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_ids().len()
+    }
+
+    // Flattened edges, so `a -> b -> c` counts as two
+    pub fn edge_count(&self) -> usize {
+        self.edges().len()
+    }
+
+    // True when `id` is declared or used by an edge anywhere in the graph
+    pub fn contains_node(&self, id: &str) -> bool {
+        mentions_node(self.statements.as_deref().unwrap_or_default(), id)
+    }
+
+    // Direction matters for digraphs only
+    pub fn contains_edge(&self, from: &str, to: &str) -> bool {
+        let key = self.edge_key(from, to);
+        self.edges()
+            .iter()
+            .any(|edge| self.edge_key(&edge.from.id, &edge.to.id) == key)
+    }
+
+    // Distinct ports the edges use on `node`, in document order
+    pub fn ports_of(&self, node: &str) -> Vec<Port> {
+        let mut ports: Vec<Port> = vec![];
+        for edge in self.edges() {
+            for node_id in [edge.from, edge.to] {
+                if node_id.id != node {
+                    continue;
+                }
+                if let Some(port) = &node_id.port {
+                    if !ports.contains(port) {
+                        ports.push(port.clone());
+                    }
+                }
+            }
+        }
+        ports
+    }
+
+    // Ids used by edges that never get a node statement, in order of first use
+    // Nodes listed in a subgraph endpoint like {a b} -> c count as declared
+    pub fn undeclared_endpoints(&self) -> Vec<String> {
+        let mut declared: HashSet<&str> = HashSet::new();
+        let mut stack: Vec<&[Statement]> = vec![self.statements.as_deref().unwrap_or_default()];
+        while let Some(statements) = stack.pop() {
+            for statement in statements {
+                match statement {
+                    Statement::NodeStmt(node_stmt) => {
+                        declared.insert(&node_stmt.id.id);
+                    }
+                    Statement::EdgeStmt(edge_stmt) => {
+                        for side in edge_sides(edge_stmt) {
+                            if let EdgeStmtSide::SubGraph(subgraph) = side {
+                                stack.push(&subgraph.statements);
+                            }
+                        }
+                    }
+                    Statement::SubGraph(subgraph) => stack.push(&subgraph.statements),
+                    Statement::AttrStmt(_) | Statement::AttributeStmt(_) => {}
+                }
+            }
+        }
+
+        let mut undeclared: Vec<String> = vec![];
+        let mut seen: HashSet<&str> = HashSet::new();
+        for edge in self.edges() {
+            for node_id in [edge.from, edge.to] {
+                if !declared.contains(node_id.id.as_str()) && seen.insert(&node_id.id) {
+                    undeclared.push(node_id.id.clone());
+                }
+            }
+        }
+        undeclared
+    }
+
+    // Every explicitly set attribute in document order, tagged with what it was set on
+    // Attributes of subgraphs count as graph attributes, node [...] and edge [...] defaults are left out
+    pub fn all_attributes(&self) -> Vec<(AttrScope, String, String)> {
+        let mut attributes = vec![];
+        if let Some(statements) = &self.statements {
+            collect_attributes(statements, &mut attributes);
+        }
+        attributes
+    }
+
+    // The graph's rankdir, TB when it is missing or not recognized
+    pub fn rankdir(&self) -> RankDir {
+        self.rankdir_with_lint().0
+    }
+
+    // Same as rankdir, plus a lint when the value was not recognized
+    pub fn rankdir_with_lint(&self) -> (RankDir, Option<Lint>) {
+        let Some(value) = self.graph_attrs().remove("rankdir") else {
+            return (RankDir::default(), None);
+        };
+        match RankDir::from_value(&value) {
+            Some(rankdir) => (rankdir, None),
+            None => (
+                RankDir::default(),
+                Some(Lint::new(
+                    LintKind::UnknownRankDir,
+                    format!("Unknown rankdir {}, using TB", value),
+                )),
+            ),
+        }
+    }
+
+    // True when both nodes sit in the same innermost `cluster*` subgraph
+    // A node mentioned in several clusters belongs to the first one, nodes outside
+    // every cluster are never in the same cluster as anything
+    pub fn same_cluster(&self, a: &str, b: &str) -> bool {
+        let clusters = self.cluster_memberships();
+        match (clusters.get(a), clusters.get(b)) {
+            (Some(Some(a_cluster)), Some(Some(b_cluster))) => a_cluster == b_cluster,
+            _ => false,
+        }
+    }
+
+    // Every node mapped to the id of its innermost `cluster*` subgraph, None outside every cluster
+    fn cluster_memberships(&self) -> HashMap<&str, Option<&str>> {
+        let mut clusters: HashMap<&str, Option<&str>> = HashMap::new();
+        collect_memberships(
+            self.statements.as_deref().unwrap_or_default(),
+            None,
+            SubGraph::is_cluster,
+            &mut clusters,
+        );
+        clusters
+    }
+
+    // Every node mapped to the id of its innermost named subgraph, None for top level nodes
+    // Anonymous subgraphs like `{a b}` only group and do not count, a node mentioned both at the
+    // top level and in subgraphs belongs to the first subgraph it is mentioned in
+    pub fn node_cluster_map(&self) -> HashMap<String, Option<String>> {
+        let mut memberships: HashMap<&str, Option<&str>> = HashMap::new();
+        collect_memberships(
+            self.statements.as_deref().unwrap_or_default(),
+            None,
+            |subgraph| subgraph.id.is_some(),
+            &mut memberships,
+        );
+        memberships
+            .into_iter()
+            .map(|(id, subgraph)| (id.to_string(), subgraph.map(str::to_string)))
+            .collect()
+    }
+
+    // Edges whose ends sit in different innermost `cluster*` subgraphs, in document order
+    // An edge between a cluster and the top level crosses, one between two nodes outside every
+    // cluster does not; other named subgraphs like `subgraph same { rank=same }` do not count
+    pub fn inter_cluster_edges(&self) -> Vec<(String, String)> {
+        let clusters = self.cluster_memberships();
+        self.edges()
+            .into_iter()
+            .filter(|edge| clusters.get(edge.from.id.as_str()) != clusters.get(edge.to.id.as_str()))
+            .map(|edge| (edge.from.id.clone(), edge.to.id.clone()))
+            .collect()
+    }
+
+    // Top level graph attributes, from both `graph [k=v]` and `k=v` statements; later ones win
+    pub fn graph_attrs(&self) -> HashMap<String, String> {
+        let mut attrs: HashMap<String, String> = HashMap::new();
+        for statement in self.statements.iter().flatten() {
+            match statement {
+                Statement::AttrStmt(attr_stmt) if attr_stmt.kind == AttrStmtKind::Graph => {
+                    for attribute in &attr_stmt.attr_list.items {
+                        attrs.insert(attribute.lhs.clone(), attribute.rhs.clone());
+                    }
+                }
+                Statement::AttributeStmt(attribute_stmt) => {
+                    attrs.insert(attribute_stmt.lhs.clone(), attribute_stmt.rhs.clone());
+                }
+                _ => {}
+            }
+        }
+        attrs
+    }
+
+    pub(crate) fn uses_edge_op(&self, op: &EdgeOp) -> bool {
+        let mut ops: Vec<(&Statement, Vec<EdgeOp>)> = vec![];
+        collect_edge_ops(self.statements.as_deref().unwrap_or_default(), &mut ops);
+        ops.iter().any(|(_, stmt_ops)| stmt_ops.contains(op))
+    }
+
+    // The edge operator every edge uses, or one error per edge statement using the other
+    // operator when both appear. The graph type decides which operator is the right one
+    pub fn edge_op_consistency(&self) -> Result<EdgeOp, Vec<ParserError>> {
+        let expected = if self.is_directed() {
+            EdgeOp::Directed
+        } else {
+            EdgeOp::UnDirected
+        };
+        let mut ops: Vec<(&Statement, Vec<EdgeOp>)> = vec![];
+        collect_edge_ops(self.statements.as_deref().unwrap_or_default(), &mut ops);
+
+        let mut all_ops = ops.iter().flat_map(|(_, stmt_ops)| stmt_ops);
+        let Some(first_op) = all_ops.next() else {
+            return Ok(expected);
+        };
+        if all_ops.all(|op| op == first_op) {
+            return Ok(first_op.clone());
+        }
+        let mut errors: Vec<ParserError> = vec![];
+        for (statement, stmt_ops) in &ops {
+            let Some(op) = stmt_ops.iter().find(|op| **op != expected) else {
+                continue;
+            };
+            let token = match op {
+                EdgeOp::Directed => Delimiter::DirectedEdge,
+                EdgeOp::UnDirected => Delimiter::UndirectedEdge,
+            };
+            errors.push(ParserError {
+                token: Some(Token::Delimiter(token)),
+                span: None,
+                reason: Some(format!(
+                    "Edge `{}` uses {} but the graph uses {}",
+                    statement, op, expected
+                )),
+            });
+        }
+        Err(errors)
+    }
+}
+
+// Edge statements with their operators, in document order
+fn collect_edge_ops<'a>(statements: &'a [Statement], ops: &mut Vec<(&'a Statement, Vec<EdgeOp>)>) {
+    for statement in statements {
+        match statement {
+            Statement::EdgeStmt(edge_stmt) => {
+                for side in edge_sides(edge_stmt) {
+                    if let EdgeStmtSide::SubGraph(subgraph) = side {
+                        collect_edge_ops(&subgraph.statements, ops);
+                    }
+                }
+                let mut stmt_ops = vec![];
+                let mut rhs: Option<&EdgeRhs> = Some(&edge_stmt.edge_rhs);
+                while let Some(current) = rhs {
+                    stmt_ops.push(current.edge_op.clone());
+                    rhs = current.edge_optional.as_deref();
+                }
+                ops.push((statement, stmt_ops));
+            }
+            Statement::SubGraph(subgraph) => collect_edge_ops(&subgraph.statements, ops),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::grammer::Compass, parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    fn edge_pairs(dg: &DotGraph) -> Vec<(String, String)> {
+        dg.edges()
+            .iter()
+            .map(|edge| (edge.from.id.clone(), edge.to.id.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_edges_chain() {
+        let tokens = tokenize("digraph { a -> b -> c; }".to_string()).unwrap();
+        let dg = parse(&tokens).unwrap();
+        assert_eq!(
+            edge_pairs(&dg),
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("b".to_string(), "c".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edges_in_subgraph() {
+        let tokens = tokenize("graph { subgraph s { a -- b } c -- {d e} }".to_string()).unwrap();
+        let dg = parse(&tokens).unwrap();
+        assert_eq!(
+            edge_pairs(&dg),
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("c".to_string(), "d".to_string()),
+                ("c".to_string(), "e".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edges_from_subgraph_with_inner_edges() {
+        let pair = |from: &str, to: &str| (from.to_string(), to.to_string());
+        let dg = parse(&tokenize("digraph { {a -> b} -> c }".to_string()).unwrap()).unwrap();
+        assert_eq!(
+            edge_pairs(&dg),
+            vec![pair("a", "b"), pair("a", "c"), pair("b", "c")]
+        );
+        assert_eq!(dg.to_string(), "digraph {\n    {a -> b} -> c;\n}");
+
+        // inner edges on both ends and in the middle of a chain
+        let code = "digraph { subgraph s {a -> b} -> {c -> d} -> e }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(
+            edge_pairs(&dg),
+            vec![
+                pair("a", "b"),
+                pair("c", "d"),
+                pair("a", "c"),
+                pair("a", "d"),
+                pair("b", "c"),
+                pair("b", "d"),
+                pair("c", "e"),
+                pair("d", "e")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subgraph_node_ids() {
+        let tokens = tokenize("graph { subgraph s { a; a -- b; c } }".to_string()).unwrap();
+        let dg = parse(&tokens).unwrap();
+        let statements = dg.statements.unwrap();
+        let Statement::SubGraph(subgraph) = &statements[0] else {
+            panic!("Expected a subgraph, got {:?}", statements[0]);
+        };
+        let ids: Vec<&str> = subgraph.node_ids().iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_parallel_edge_counts_directed() {
+        let tokens = tokenize("digraph { a->b; a->b; a->c }".to_string()).unwrap();
+        let dg = parse(&tokens).unwrap();
+        let counts = dg.parallel_edge_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&("a".to_string(), "b".to_string())], 2);
+        assert_eq!(counts[&("a".to_string(), "c".to_string())], 1);
+    }
+
+    #[test]
+    fn test_parallel_edge_counts_undirected() {
+        let tokens = tokenize("graph { a--b; b--a }".to_string()).unwrap();
+        let dg = parse(&tokens).unwrap();
+        let counts = dg.parallel_edge_counts();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&("a".to_string(), "b".to_string())], 2);
+    }
+
+    #[test]
+    fn test_graph_attrs_from_both_forms() {
+        let attr_stmt =
+            parse(&tokenize("graph { graph [bgcolor=white, rankdir=LR] }".to_string()).unwrap())
+                .unwrap();
+        let attribute_stmt =
+            parse(&tokenize("graph { bgcolor=white; rankdir=LR }".to_string()).unwrap()).unwrap();
+        assert!(matches!(
+            attr_stmt.statements.as_ref().unwrap()[0],
+            Statement::AttrStmt(_)
+        ));
+        assert!(matches!(
+            attribute_stmt.statements.as_ref().unwrap()[0],
+            Statement::AttributeStmt(_)
+        ));
+        let expected = HashMap::from([
+            ("bgcolor".to_string(), "white".to_string()),
+            ("rankdir".to_string(), "LR".to_string()),
+        ]);
+        assert_eq!(attr_stmt.graph_attrs(), expected);
+        assert_eq!(attribute_stmt.graph_attrs(), expected);
+    }
+
+    #[test]
+    fn test_graph_attrs_ignores_node_defaults_and_subgraphs() {
+        let tokens = tokenize(
+            "graph { node [shape=box]; subgraph { color=red } graph [label=x]; label=y }"
+                .to_string(),
+        )
+        .unwrap();
+        let dg = parse(&tokens).unwrap();
+        assert_eq!(
+            dg.graph_attrs(),
+            HashMap::from([("label".to_string(), "y".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_enumerate_edges_in_source_order() {
+        let code = "digraph { a -> b -> c; subgraph s { d -> e } x; {f g} -> h; i -> j }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let indexed: Vec<(usize, String, String)> = dg
+            .enumerate_edges()
+            .map(|(idx, edge)| (idx, edge.from.id.clone(), edge.to.id.clone()))
+            .collect();
+        let expected = ["a", "b", "b", "c", "d", "e", "f", "h", "g", "h", "i", "j"]
+            .chunks(2)
+            .enumerate()
+            .map(|(idx, pair)| (idx, pair[0].to_string(), pair[1].to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(indexed, expected);
+    }
+
+    #[test]
+    fn test_sorted_edges_is_order_independent() {
+        let first =
+            parse(&tokenize("graph { c -- a; b -- a; a -- b }".to_string()).unwrap()).unwrap();
+        let second =
+            parse(&tokenize("graph { b -- a; a -- c; a -- b }".to_string()).unwrap()).unwrap();
+        let expected = vec![
+            ("a".to_string(), "b".to_string()),
+            ("a".to_string(), "b".to_string()),
+            ("a".to_string(), "c".to_string()),
+        ];
+        assert_eq!(first.sorted_edges(), expected);
+        assert_eq!(second.sorted_edges(), expected);
+
+        let directed = parse(&tokenize("digraph { c -> a; a -> b }".to_string()).unwrap()).unwrap();
+        assert_eq!(
+            directed.sorted_edges(),
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("c".to_string(), "a".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edges_long_chain() {
+        let chain: Vec<String> = (0..50).map(|idx| format!("n{}", idx)).collect();
+        let code = format!("digraph {{ {} }}", chain.join(" -> "));
+        let dg = parse(&tokenize(code).unwrap()).unwrap();
+
+        let Statement::EdgeStmt(edge_stmt) = &dg.statements.as_ref().unwrap()[0] else {
+            panic!("Expected an edge statement");
+        };
+        assert_eq!(edge_sides(edge_stmt).len(), 50);
+
+        let pairs = edge_pairs(&dg);
+        assert_eq!(pairs.len(), 49);
+        for (idx, (from, to)) in pairs.iter().enumerate() {
+            assert_eq!(from, &format!("n{}", idx));
+            assert_eq!(to, &format!("n{}", idx + 1));
+        }
+    }
+
+    #[test]
+    fn test_ports_of() {
+        let code = "digraph { a:p1 -> b; a:p2 -> c; c -> a:p1; a:n -> d:s }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let ports: Vec<String> = dg.ports_of("a").iter().map(|p| p.to_string()).collect();
+        assert_eq!(ports, vec![":p1", ":p2", ":n"]);
+        assert_eq!(dg.ports_of("b"), vec![]);
+        assert_eq!(dg.ports_of("d").len(), 1);
+    }
+
+    #[test]
+    fn test_id_or_default() {
+        let named = parse(&tokenize("digraph deps { a }".to_string()).unwrap()).unwrap();
+        assert_eq!(named.id(), Some("deps"));
+        assert_eq!(named.id_or_default(), "deps");
+
+        let anonymous = parse(&tokenize("digraph { a }".to_string()).unwrap()).unwrap();
+        assert_eq!(anonymous.id(), None);
+        assert_eq!(anonymous.id_or_default(), "G");
+    }
+
+    #[test]
+    fn test_undeclared_endpoints() {
+        let dg = parse(&tokenize("digraph { a -> b }".to_string()).unwrap()).unwrap();
+        assert_eq!(dg.undeclared_endpoints(), vec!["a", "b"]);
+
+        let code = "digraph { a; subgraph s { c [shape=box] } a -> b -> c; {d e} -> f }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.undeclared_endpoints(), vec!["b", "f"]);
+    }
+
+    #[test]
+    fn test_all_attributes_scopes() {
+        let code = "digraph {
+            rankdir=LR; node [shape=box];
+            a [color=red];
+            a -> {b c [x=1]} [weight=2];
+            subgraph s { graph [label=inner]; d [y=3] }
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let attr =
+            |scope: AttrScope, key: &str, value: &str| (scope, key.to_string(), value.to_string());
+        let node = |id: &str| AttrScope::Node(id.to_string());
+        let edge = |from: &str, to: &str| AttrScope::Edge(from.to_string(), to.to_string());
+        assert_eq!(
+            dg.all_attributes(),
+            vec![
+                attr(AttrScope::Graph, "rankdir", "LR"),
+                attr(node("a"), "color", "red"),
+                attr(node("c"), "x", "1"),
+                attr(edge("a", "b"), "weight", "2"),
+                attr(edge("a", "c"), "weight", "2"),
+                attr(AttrScope::Graph, "label", "inner"),
+                attr(node("d"), "y", "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_contains_node_and_edge() {
+        let code = "digraph { a; subgraph s { b -> c } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert!(dg.contains_node("a"));
+        assert!(dg.contains_node("c"));
+        assert!(!dg.contains_node("d"));
+        let dg = parse(&tokenize("digraph { x -> {y z} }".to_string()).unwrap()).unwrap();
+        assert!(dg.contains_node("z"));
+        assert!(!dg.contains_node("s"));
+        let code = "digraph { a; subgraph s { b -> c } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert!(dg.contains_edge("b", "c"));
+        assert!(!dg.contains_edge("c", "b"));
+        assert!(!dg.contains_edge("a", "b"));
+
+        let code = "graph { a -- b }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert!(dg.contains_edge("a", "b"));
+        assert!(dg.contains_edge("b", "a"));
+        assert!(!dg.contains_edge("a", "a"));
+    }
+
+    #[test]
+    fn test_edges_with_empty_subgraph_endpoint() {
+        // an empty endpoint has no nodes, so the hops on either side of it produce no edges
+        let code = "digraph { a -> {}; b -> subgraph s {} -> c; d -> {} -> e -> f }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(edge_pairs(&dg), vec![("e".to_string(), "f".to_string())]);
+        assert_eq!(
+            dg.to_string(),
+            "digraph {
+    a -> {};
+    b -> subgraph s {} -> c;
+    d -> {} -> e -> f;
+}"
+        );
+    }
+
+    #[test]
+    fn test_rankdir() {
+        let rankdir = |code: &str| {
+            parse(&tokenize(code.to_string()).unwrap())
+                .unwrap()
+                .rankdir_with_lint()
+        };
+        assert_eq!(rankdir("digraph { a }"), (RankDir::TB, None));
+        assert_eq!(rankdir("digraph { rankdir=TB }"), (RankDir::TB, None));
+        assert_eq!(rankdir("digraph { rankdir=LR }"), (RankDir::LR, None));
+        assert_eq!(
+            rankdir("digraph { graph [rankdir=BT] }"),
+            (RankDir::BT, None)
+        );
+        assert_eq!(rankdir("digraph { rankdir=rl }"), (RankDir::RL, None));
+
+        let (fallback, lint) = rankdir("digraph { rankdir=sideways }");
+        assert_eq!(fallback, RankDir::TB);
+        assert_eq!(lint.unwrap().kind, LintKind::UnknownRankDir);
+    }
+
+    #[test]
+    fn test_graph_with_only_attributes() {
+        let code = "graph { bgcolor=white; rankdir=LR }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.node_count(), 0);
+        assert_eq!(dg.edge_count(), 0);
+        assert_eq!(dg.graph_attrs().len(), 2);
+        assert!(dg.adjacency_list().is_empty());
+        assert_eq!(dg.density(), 0.0);
+        assert_eq!(dg.rankdir(), RankDir::LR);
+        assert_eq!(
+            dg.to_string(),
+            "graph {\n    bgcolor=white;\n    rankdir=LR;\n}"
+        );
+        assert_eq!(dg.canonicalize(), dg);
+    }
+
+    #[test]
+    fn test_edges_keep_port_and_compass_on_both_ends() {
+        let code = "digraph { a:p:n -> b:q:s -> c:w [color=red] }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let edges = dg.edges();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(
+            edges[0].from.port,
+            Some(Port {
+                id: Some("p".to_string()),
+                compass: Some(Compass::N)
+            })
+        );
+        assert_eq!(
+            edges[0].to.port,
+            Some(Port {
+                id: Some("q".to_string()),
+                compass: Some(Compass::S)
+            })
+        );
+        assert_eq!(edges[1].from, edges[0].to);
+        assert_eq!(
+            edges[1].to.port,
+            Some(Port {
+                id: None,
+                compass: Some(Compass::W)
+            })
+        );
+        assert_eq!(edges[1].attributes.len(), 1);
+        assert_eq!(
+            dg.to_string(),
+            "digraph {\n    a:p:n -> b:q:s -> c:w [color=red];\n}"
+        );
+    }
+
+    #[test]
+    fn test_edge_op_consistency() {
+        let dg = parse(&tokenize("digraph { a -> b -> c }".to_string()).unwrap()).unwrap();
+        assert_eq!(dg.edge_op_consistency().unwrap(), EdgeOp::Directed);
+        let dg = parse(&tokenize("graph { a }".to_string()).unwrap()).unwrap();
+        assert_eq!(dg.edge_op_consistency().unwrap(), EdgeOp::UnDirected);
+    }
+
+    #[test]
+    fn test_edge_op_consistency_mixed_operators() {
+        let code = "digraph { a -> b; b -- c; c -> d -- e; subgraph s { e -- f } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let errors = dg.edge_op_consistency().unwrap_err();
+        let reasons: Vec<&str> = errors
+            .iter()
+            .map(|err| err.reason.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "Edge `b -- c` uses -- but the graph uses ->",
+                "Edge `c -> d -- e` uses -- but the graph uses ->",
+                "Edge `e -- f` uses -- but the graph uses ->",
+            ]
+        );
+        assert_eq!(
+            errors[0].token,
+            Some(Token::Delimiter(Delimiter::UndirectedEdge))
+        );
+    }
+
+    #[test]
+    fn test_node_ids_in_order() {
+        let code = "digraph { b -> a; c; a [color=red]; subgraph s { d -> b } e }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.node_ids_in_order(), vec!["b", "a", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_declared_node_ids() {
+        let code = "digraph { b -> a; c; a [color=red]; subgraph s { d -> b; f } e -> {g} }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let ids = |node_ids: Vec<&NodeId>| {
+            node_ids
+                .into_iter()
+                .map(|node_id| node_id.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(dg.node_ids()), vec!["b", "a", "c", "d", "f", "e", "g"]);
+        assert_eq!(ids(dg.declared_node_ids()), vec!["c", "a", "f", "g"]);
+        assert!(DotGraph::default().declared_node_ids().is_empty());
+    }
+
+    #[test]
+    fn test_quoted_cluster_ids() {
+        let code = "digraph { subgraph \"Cluster One\" { a; b } subgraph \"cluster_x\" { c; d } subgraph \"my cluster\" { e } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let subgraphs: Vec<&SubGraph> = dg
+            .statements
+            .iter()
+            .flatten()
+            .filter_map(Statement::as_subgraph)
+            .collect();
+        assert_eq!(subgraphs[0].id.as_deref(), Some("Cluster One"));
+        assert!(subgraphs[0].is_cluster());
+        assert!(subgraphs[1].is_cluster());
+        assert!(!subgraphs[2].is_cluster());
+        assert!(dg.same_cluster("a", "b") && dg.same_cluster("c", "d"));
+        assert!(!dg.same_cluster("b", "c"));
+
+        let printed = dg.to_string();
+        assert!(printed.contains("subgraph \"Cluster One\" {"));
+        assert!(printed.contains("subgraph cluster_x {"));
+        assert_eq!(parse(&tokenize(printed).unwrap()).unwrap(), dg);
+    }
+
+    #[test]
+    fn test_same_cluster() {
+        let code = "digraph {
+            subgraph cluster_0 { a; b; subgraph cluster_inner { c } subgraph plain { d } }
+            subgraph cluster_1 { e -> {f} }
+            a -> g; h
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert!(dg.same_cluster("a", "b"));
+        assert!(dg.same_cluster("a", "d"));
+        assert!(dg.same_cluster("e", "f"));
+        assert!(!dg.same_cluster("a", "c"));
+        assert!(!dg.same_cluster("a", "e"));
+        assert!(!dg.same_cluster("a", "g"));
+        assert!(!dg.same_cluster("g", "h"));
+        assert!(!dg.same_cluster("a", "missing"));
+    }
+
+    #[test]
+    fn test_inter_cluster_edges() {
+        let code = "digraph {
+            subgraph cluster_a { a1 -> a2 }
+            subgraph cluster_b { b1 -> b2 }
+            a2 -> b1; x -> y; y -> a1; b2 -> b2
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let pair = |from: &str, to: &str| (from.to_string(), to.to_string());
+        assert_eq!(
+            dg.inter_cluster_edges(),
+            vec![pair("a2", "b1"), pair("y", "a1")]
+        );
+        assert!(DotGraph::default().inter_cluster_edges().is_empty());
+    }
+
+    #[test]
+    fn test_inter_cluster_edges_ignores_other_subgraphs() {
+        let code = "digraph {
+            subgraph same_rank { rank=same; b; c }
+            a -> b; b -> c
+            subgraph cluster_x { subgraph inner { d } e }
+            d -> e
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert!(dg.inter_cluster_edges().is_empty());
+    }
+
+    #[test]
+    fn test_node_cluster_map() {
+        let code = "digraph {
+            subgraph outer { a; subgraph inner { b -> {c} } }
+            d -> b; subgraph { e } d -> f
+            subgraph cluster_x { d }
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let map = dg.node_cluster_map();
+        let expected: HashMap<String, Option<String>> = [
+            ("a", Some("outer")),
+            ("b", Some("inner")),
+            ("c", Some("inner")),
+            ("d", Some("cluster_x")),
+            ("e", None),
+            ("f", None),
+        ]
+        .into_iter()
+        .map(|(id, subgraph)| (id.to_string(), subgraph.map(str::to_string)))
+        .collect();
+        assert_eq!(map, expected);
+    }
+}
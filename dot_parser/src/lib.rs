@@ -1,2 +1,13 @@
+pub mod analysis;
+pub mod charset;
+pub mod color;
+pub mod defaults;
+pub mod display;
+pub mod graph;
+pub mod lint;
 pub mod parser;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod tokenizer;
+pub mod transform;
+pub mod visit;
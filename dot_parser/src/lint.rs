@@ -0,0 +1,386 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    graph::edge_sides,
+    parser::grammer::{DotGraph, EdgeStmtSide, Statement},
+    tokenizer::{lex, Delimiter, Token},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintKind {
+    // rankdir is set to something other than TB, LR, BT or RL
+    UnknownRankDir,
+    // an undirected graph with -> edges was parsed as a digraph
+    CoercedGraphType,
+    // a -- edge in a digraph was rewritten to ->
+    FixedEdgeOp,
+    // the same node id is written both quoted and unquoted, e.g. a and "a"
+    MixedQuoting,
+    // tokens after the closing brace of the graph were ignored
+    TrailingTokens,
+    // an attribute name Graphviz does not know, usually a typo
+    UnknownAttribute,
+}
+
+// A problem worth reporting that still lets the graph be used
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub message: String,
+}
+
+impl Lint {
+    pub fn new(kind: LintKind, message: String) -> Self {
+        Self { kind, message }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.kind, self.message)
+    }
+}
+
+// Node ids are compared by their text without quotes, so a and "a" are one node
+// Lints every node id the source writes both ways, which usually means one of them was meant
+// to be a different node. Works on the source since the tokens no longer know about quotes
+pub fn lint_mixed_quoting(code: &str) -> Vec<Lint> {
+    let (tokens, _) = lex(code);
+    // (quoted, unquoted) per id, in first-mention order
+    let mut order: Vec<&str> = vec![];
+    let mut seen: HashMap<&str, (bool, bool)> = HashMap::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        let Token::Identifier(id) = &token.value else {
+            continue;
+        };
+        // attribute names and values, ports, and graph and subgraph ids are not node ids
+        let after_id_delimiter = idx > 0
+            && matches!(
+                &tokens[idx - 1].value,
+                Token::Keyword(_) | Token::Delimiter(Delimiter::Equal | Delimiter::Colon)
+            );
+        let before_equal = tokens
+            .get(idx + 1)
+            .is_some_and(|next| next.value == Token::Delimiter(Delimiter::Equal));
+        if after_id_delimiter || before_equal {
+            continue;
+        }
+        let quoted = code[token.span.start..].starts_with('"');
+        let forms = seen.entry(id).or_insert_with(|| {
+            order.push(id);
+            (false, false)
+        });
+        if quoted {
+            forms.0 = true;
+        } else {
+            forms.1 = true;
+        }
+    }
+    order
+        .into_iter()
+        .filter(|id| seen[id] == (true, true))
+        .map(|id| {
+            Lint::new(
+                LintKind::MixedQuoting,
+                format!("Node {} is written both as {} and \"{}\"", id, id, id),
+            )
+        })
+        .collect()
+}
+
+// Attribute names from the Graphviz documentation, names are case sensitive
+pub const KNOWN_ATTRIBUTES: [&str; 177] = [
+    "_background",
+    "area",
+    "arrowhead",
+    "arrowsize",
+    "arrowtail",
+    "bb",
+    "beautify",
+    "bgcolor",
+    "center",
+    "charset",
+    "class",
+    "cluster",
+    "clusterrank",
+    "color",
+    "colorscheme",
+    "comment",
+    "compound",
+    "concentrate",
+    "constraint",
+    "Damping",
+    "decorate",
+    "defaultdist",
+    "dim",
+    "dimen",
+    "dir",
+    "diredgeconstraints",
+    "distortion",
+    "dpi",
+    "edgehref",
+    "edgetarget",
+    "edgetooltip",
+    "edgeURL",
+    "epsilon",
+    "esep",
+    "fillcolor",
+    "fixedsize",
+    "fontcolor",
+    "fontname",
+    "fontnames",
+    "fontpath",
+    "fontsize",
+    "forcelabels",
+    "gradientangle",
+    "group",
+    "head_lp",
+    "headclip",
+    "headhref",
+    "headlabel",
+    "headport",
+    "headtarget",
+    "headtooltip",
+    "headURL",
+    "height",
+    "href",
+    "id",
+    "image",
+    "imagepath",
+    "imagepos",
+    "imagescale",
+    "inputscale",
+    "K",
+    "label",
+    "label_scheme",
+    "labelangle",
+    "labeldistance",
+    "labelfloat",
+    "labelfontcolor",
+    "labelfontname",
+    "labelfontsize",
+    "labelhref",
+    "labeljust",
+    "labelloc",
+    "labeltarget",
+    "labeltooltip",
+    "labelURL",
+    "landscape",
+    "layer",
+    "layerlistsep",
+    "layers",
+    "layerselect",
+    "layersep",
+    "layout",
+    "len",
+    "levels",
+    "levelsgap",
+    "lhead",
+    "lheight",
+    "linelength",
+    "lp",
+    "ltail",
+    "lwidth",
+    "margin",
+    "maxiter",
+    "mclimit",
+    "mindist",
+    "minlen",
+    "mode",
+    "model",
+    "newrank",
+    "nodesep",
+    "nojustify",
+    "normalize",
+    "notranslate",
+    "nslimit",
+    "nslimit1",
+    "oneblock",
+    "ordering",
+    "orientation",
+    "outputorder",
+    "overlap",
+    "overlap_scaling",
+    "overlap_shrink",
+    "pack",
+    "packmode",
+    "pad",
+    "page",
+    "pagedir",
+    "pencolor",
+    "penwidth",
+    "peripheries",
+    "pin",
+    "pos",
+    "quadtree",
+    "quantum",
+    "radius",
+    "rank",
+    "rankdir",
+    "ranksep",
+    "ratio",
+    "rects",
+    "regular",
+    "remincross",
+    "repulsiveforce",
+    "resolution",
+    "root",
+    "rotate",
+    "rotation",
+    "samehead",
+    "sametail",
+    "samplepoints",
+    "scale",
+    "searchsize",
+    "sep",
+    "shape",
+    "shapefile",
+    "showboxes",
+    "sides",
+    "size",
+    "skew",
+    "smoothing",
+    "sortv",
+    "splines",
+    "start",
+    "style",
+    "stylesheet",
+    "tail_lp",
+    "tailclip",
+    "tailhref",
+    "taillabel",
+    "tailport",
+    "tailtarget",
+    "tailtooltip",
+    "tailURL",
+    "target",
+    "TBbalance",
+    "tooltip",
+    "truecolor",
+    "URL",
+    "vertices",
+    "viewport",
+    "voro_margin",
+    "weight",
+    "width",
+    "xdotversion",
+    "xlabel",
+    "xlp",
+    "z",
+];
+
+// Every attribute name set in the statements, at any depth, in the order they first appear
+fn collect_attribute_names<'a>(statements: &'a [Statement], names: &mut Vec<&'a str>) {
+    let add = |name: &'a str, names: &mut Vec<&'a str>| {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    };
+    for statement in statements {
+        match statement {
+            Statement::NodeStmt(node_stmt) => {
+                for attribute in node_stmt.attributes.iter().flatten() {
+                    add(&attribute.lhs, names);
+                }
+            }
+            Statement::EdgeStmt(edge_stmt) => {
+                for side in edge_sides(edge_stmt) {
+                    if let EdgeStmtSide::SubGraph(subgraph) = side {
+                        collect_attribute_names(&subgraph.statements, names);
+                    }
+                }
+                for attribute in edge_stmt.attributes.iter().flatten() {
+                    add(&attribute.lhs, names);
+                }
+            }
+            Statement::AttrStmt(attr_stmt) => {
+                for attribute in &attr_stmt.attr_list.items {
+                    add(&attribute.lhs, names);
+                }
+            }
+            Statement::AttributeStmt(attribute_stmt) => add(&attribute_stmt.lhs, names),
+            Statement::SubGraph(subgraph) => collect_attribute_names(&subgraph.statements, names),
+        }
+    }
+}
+
+impl DotGraph {
+    // One lint per attribute name not in KNOWN_ATTRIBUTES, in the order they first appear
+    // Unknown names are still valid DOT, Graphviz ignores them with a warning
+    pub fn validate_attribute_names(&self) -> Vec<Lint> {
+        let mut names = vec![];
+        collect_attribute_names(self.statements.as_deref().unwrap_or_default(), &mut names);
+        names
+            .into_iter()
+            .filter(|name| !KNOWN_ATTRIBUTES.contains(name))
+            .map(|name| {
+                Lint::new(
+                    LintKind::UnknownAttribute,
+                    format!("Unknown attribute {}", name),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    #[test]
+    fn test_lint_mixed_quoting() {
+        let code = "digraph g { a -> \"b\"; \"a\" -> b; c [label=\"c\"]; \"d\" -> \"d\":d; e:p }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.node_ids_in_order(), vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(dg.adjacency_list()["a"], vec!["b"]);
+
+        let lints = lint_mixed_quoting(code);
+        assert_eq!(
+            lints
+                .iter()
+                .map(|lint| lint.message.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Node a is written both as a and \"a\"",
+                "Node b is written both as b and \"b\""
+            ]
+        );
+        assert!(lints.iter().all(|lint| lint.kind == LintKind::MixedQuoting));
+        assert!(lint_mixed_quoting("graph { \"a\" -- \"b\"; a [a=a] }").len() == 1);
+        assert!(lint_mixed_quoting("graph { \"a\" -- \"b\"; b [a=a] }").len() == 1);
+        assert!(lint_mixed_quoting("graph { \"x\" -- y [x=\"y\"] }").is_empty());
+    }
+
+    #[test]
+    fn test_validate_attribute_names() {
+        let code = "digraph {
+            colour=red; node [colour=blue, shape=box];
+            a [color=red, colr=x]; a -> b [colour=green, weight=2]
+            subgraph s { label=S; Color=red }
+            b -> {c [shap=box]}
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let lints = dg.validate_attribute_names();
+        assert_eq!(
+            lints
+                .iter()
+                .map(|lint| lint.message.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Unknown attribute colour",
+                "Unknown attribute colr",
+                "Unknown attribute Color",
+                "Unknown attribute shap"
+            ]
+        );
+        assert!(lints
+            .iter()
+            .all(|lint| lint.kind == LintKind::UnknownAttribute));
+
+        let known = "graph { color=red; a -- b [color=blue, penwidth=2, URL=\"x\"] }";
+        let dg = parse(&tokenize(known.to_string()).unwrap()).unwrap();
+        assert!(dg.validate_attribute_names().is_empty());
+    }
+}
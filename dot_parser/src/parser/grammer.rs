@@ -1,91 +1,64 @@
-use crate::tokenizer::Token;
-
-#[derive(Debug, Clone, PartialEq)]
+use crate::tokenizer::{Span, Token};
+
+pub use super::{
+    parser_attr_list::AttrList,
+    parser_attribute::Attribute,
+    parser_attribute_stmt::{AttrStmt, AttrStmtKind},
+    parser_compass::Compass,
+    parser_node_id::NodeId,
+    parser_port::Port,
+};
+
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct SubGraph {
     pub id: Option<String>,
     pub statements: Vec<Statement>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub enum EdgeOp {
     Directed,
+    #[default]
     UnDirected,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum AttrStmtType {
-    Graph,
-    Node,
-    Edge,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct AttrStmt {
-    pub attr_stmt_type: AttrStmtType,
-    pub items: Vec<Attribute>,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum Compass {
-    N,
-    Ne,
-    E,
-    Se,
-    S,
-    Sw,
-    W,
-    Nw,
-    C,
-    Underscore,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct Port {
-    pub id: Option<String>,
-    pub compass: Option<Compass>,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct NodeId {
-    pub id: String,
-    pub port: Option<Port>,
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum EdgeStmtSide {
     NodeId(NodeId),
     SubGraph(SubGraph),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl Default for EdgeStmtSide {
+    fn default() -> Self {
+        EdgeStmtSide::NodeId(NodeId::default())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct EdgeRhs {
     pub edge_op: EdgeOp,
     pub edge_to: EdgeStmtSide,
     pub edge_optional: Option<Box<EdgeRhs>>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct EdgeStmt {
     pub edge_lhs: EdgeStmtSide,
     pub edge_rhs: EdgeRhs,
     pub attributes: Option<Vec<Attribute>>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Attribute {
-    pub lhs: String,
-    pub rhs: String,
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub struct AttributeStmt {
     pub lhs: String,
     pub rhs: String,
+    // same as Attribute::quoted_lhs
+    pub quoted_lhs: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct NodeStmt {
-    pub id: String,
+    pub id: NodeId,
     pub attributes: Option<Vec<Attribute>>,
 }
 
@@ -98,6 +71,60 @@ pub enum Statement {
     SubGraph(SubGraph),
 }
 
+impl Statement {
+    pub fn is_node(&self) -> bool {
+        matches!(self, Statement::NodeStmt(_))
+    }
+
+    pub fn is_edge(&self) -> bool {
+        matches!(self, Statement::EdgeStmt(_))
+    }
+
+    // Both `graph|node|edge [...]` and `k=v` statements
+    pub fn is_attr(&self) -> bool {
+        matches!(self, Statement::AttrStmt(_) | Statement::AttributeStmt(_))
+    }
+
+    pub fn is_subgraph(&self) -> bool {
+        matches!(self, Statement::SubGraph(_))
+    }
+
+    pub fn as_node(&self) -> Option<&NodeStmt> {
+        match self {
+            Statement::NodeStmt(node_stmt) => Some(node_stmt),
+            _ => None,
+        }
+    }
+
+    pub fn as_edge(&self) -> Option<&EdgeStmt> {
+        match self {
+            Statement::EdgeStmt(edge_stmt) => Some(edge_stmt),
+            _ => None,
+        }
+    }
+
+    pub fn as_attr_stmt(&self) -> Option<&AttrStmt> {
+        match self {
+            Statement::AttrStmt(attr_stmt) => Some(attr_stmt),
+            _ => None,
+        }
+    }
+
+    pub fn as_attribute_stmt(&self) -> Option<&AttributeStmt> {
+        match self {
+            Statement::AttributeStmt(attribute_stmt) => Some(attribute_stmt),
+            _ => None,
+        }
+    }
+
+    pub fn as_subgraph(&self) -> Option<&SubGraph> {
+        match self {
+            Statement::SubGraph(subgraph) => Some(subgraph),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum GraphType {
     Graph,
@@ -112,14 +139,40 @@ pub struct DotGraph {
     pub statements: Option<Vec<Statement>>,
 }
 
+impl DotGraph {
+    pub fn new(graph_type: GraphType) -> Self {
+        DotGraph {
+            graph_type: Some(graph_type),
+            strict_mode: false,
+            id: None,
+            statements: Some(Vec::new()),
+        }
+    }
+}
+
+impl Default for DotGraph {
+    fn default() -> Self {
+        DotGraph::new(GraphType::Graph)
+    }
+}
+
 #[derive(Debug)]
 pub struct ParserError {
     pub token: Option<Token>,
+    // Where the token is in the source, only known when parsing spanned tokens
+    pub span: Option<Span>,
     pub reason: Option<String>,
 }
 
 impl std::fmt::Display for ParserError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(span) = &self.span {
+            return write!(
+                f,
+                "Error happened at: line {}, col {}, token: {:?}, \n Reason: {:?} \n",
+                span.line, span.col, self.token, self.reason
+            );
+        }
         write!(
             f,
             "Error happened at: token: {:?}, \n Reason: {:?} \n",
@@ -127,3 +180,56 @@ impl std::fmt::Display for ParserError {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    #[test]
+    fn test_statement_classification() {
+        let code = "graph { a; a -- b; node [shape=box]; rankdir=LR; subgraph s { c } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let statements = dg.statements.unwrap();
+        let kinds: Vec<(bool, bool, bool, bool)> = statements
+            .iter()
+            .map(|statement| {
+                (
+                    statement.is_node(),
+                    statement.is_edge(),
+                    statement.is_attr(),
+                    statement.is_subgraph(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (true, false, false, false),
+                (false, true, false, false),
+                (false, false, true, false),
+                (false, false, true, false),
+                (false, false, false, true),
+            ]
+        );
+
+        assert_eq!(statements[0].as_node().unwrap().id.id, "a");
+        assert!(statements[0].as_edge().is_none());
+        assert_eq!(
+            statements[1].as_edge().unwrap().edge_rhs.edge_op,
+            EdgeOp::UnDirected
+        );
+        assert_eq!(
+            statements[2].as_attr_stmt().unwrap().kind,
+            AttrStmtKind::Node
+        );
+        assert!(statements[3].as_attr_stmt().is_none());
+        assert_eq!(statements[3].as_attribute_stmt().unwrap().rhs, "LR");
+        assert_eq!(
+            statements[4].as_subgraph().unwrap().id.as_deref(),
+            Some("s")
+        );
+        assert!(statements[4].as_node().is_none());
+    }
+}
@@ -1,30 +1,1189 @@
-use anyhow::{Ok, Result};
-use grammer::DotGraph;
+use anyhow::{bail, Ok, Result};
+use grammer::{DotGraph, EdgeOp, EdgeRhs, EdgeStmtSide, GraphType, ParserError, Statement};
+use parser::{ParseBufferItem, Parser};
+use parser_stmt_list::{parse_stmt, StmtList};
 
-mod grammer;
+pub mod grammer;
+#[allow(clippy::module_inception)]
 mod parser;
 mod parser_a_list;
 mod parser_attr_list;
-mod parser_attribute_stmt;
 mod parser_attribute;
+mod parser_attribute_stmt;
 mod parser_compass;
+mod parser_edge_stmt;
 mod parser_head;
 mod parser_node_id;
+mod parser_node_stmt;
 mod parser_port;
+mod parser_stmt_list;
+mod parser_subgraph;
+
+use crate::{
+    charset::{detect_charset, Charset},
+    lint::{Lint, LintKind},
+    tokenizer::{tokenize, tokenize_range, Delimiter, Span, Spanned, Token},
+    visit::{walk_mut, VisitorMut},
+};
+
+// Deepest subgraph nesting parse accepts, the graph body itself is depth 0
+pub const DEFAULT_MAX_DEPTH: usize = 256;
 
-use crate::tokenizer::Token;
+// The statement parsers recurse once per nested subgraph and can not report errors,
+// so nesting is checked on the tokens before parsing
+fn check_depth(tokens: &[Token], max_depth: usize) -> Result<()> {
+    check_depth_within(tokens, 0, max_depth)
+}
+
+// Same as check_depth for tokens that sit inside `depth` braces that are already open
+fn check_depth_within(tokens: &[Token], mut depth: usize, max_depth: usize) -> Result<()> {
+    for token in tokens {
+        match token {
+            Token::Delimiter(Delimiter::OpenCurlyBrace) => {
+                depth += 1;
+                // the first brace opens the graph body
+                if depth > max_depth + 1 {
+                    bail!(ParserError {
+                        token: Some(token.clone()),
+                        span: None,
+                        reason: Some(format!(
+                            "Subgraphs are nested deeper than the limit of {}",
+                            max_depth
+                        )),
+                    });
+                }
+            }
+            Token::Delimiter(Delimiter::ClosedCurlyBrace) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Upper bounds for untrusted input, None means no limit
+// max_depth falls back to DEFAULT_MAX_DEPTH because deeper nesting would overflow the stack
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParseLimits {
+    pub max_tokens: Option<usize>,
+    pub max_statements: Option<usize>,
+    pub max_depth: Option<usize>,
+}
+
+fn limit_error(token: Option<Token>, what: &str, limit: usize) -> anyhow::Error {
+    anyhow::anyhow!(ParserError {
+        token,
+        span: None,
+        reason: Some(format!(
+            "Graph has more {} than the limit of {}",
+            what, limit
+        )),
+    })
+}
+
+// Every statement in the graph, including the ones inside subgraphs
+fn count_statements(statements: &[Statement]) -> usize {
+    let mut count = 0;
+    let mut stack: Vec<&[Statement]> = vec![statements];
+    while let Some(statements) = stack.pop() {
+        count += statements.len();
+        for statement in statements {
+            match statement {
+                Statement::SubGraph(subgraph) => stack.push(&subgraph.statements),
+                Statement::EdgeStmt(edge_stmt) => {
+                    let mut side = Some(&edge_stmt.edge_lhs);
+                    let mut rhs = Some(&edge_stmt.edge_rhs);
+                    while let Some(current) = side {
+                        if let EdgeStmtSide::SubGraph(subgraph) = current {
+                            stack.push(&subgraph.statements);
+                        }
+                        side = rhs.map(|edge_rhs| &edge_rhs.edge_to);
+                        rhs = rhs.and_then(|edge_rhs| edge_rhs.edge_optional.as_deref());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    count
+}
 
 // Creates an AST from list of tokens
 pub fn parse(tokens_vec: &[Token]) -> Result<DotGraph> {
-    let dg = parser_head::parse_head(tokens_vec).unwrap();
-    let start_idx = match (dg.strict_mode, dg.id.clone()) {
-        (true, Some(_)) => 4,
-        (false, Some(_)) => 3,
-        (true, None) => 3,
-        (false, None) => 2,
+    parse_with_limits(tokens_vec, &ParseLimits::default())
+}
+
+// Same as parse, erroring when subgraphs nest deeper than max_depth
+pub fn parse_with_max_depth(tokens_vec: &[Token], max_depth: usize) -> Result<DotGraph> {
+    parse_with_limits(
+        tokens_vec,
+        &ParseLimits {
+            max_depth: Some(max_depth),
+            ..ParseLimits::default()
+        },
+    )
+}
+
+// Same as parse, aborting as soon as one of the limits is exceeded
+pub fn parse_with_limits(tokens_vec: &[Token], limits: &ParseLimits) -> Result<DotGraph> {
+    parse_tokens(tokens_vec, &[], limits)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParseOptions {
+    // Parse a `graph` with -> edges as a digraph and report it as a lint instead of erroring
+    pub coerce_graph_type: bool,
+    // Rewrite -- edges in a digraph to -> with a lint per edge statement instead of erroring
+    pub autofix_edge_ops: bool,
+    // Parse the first graph and report whatever follows its closing brace as a lint,
+    // for DOT cut out of surrounding text
+    pub ignore_trailing_tokens: bool,
+    // Error on any statement not followed by `;`, they are optional otherwise
+    pub require_semicolons: bool,
+    // Keep edge operators that do not match the graph type as written, like parse does
+    // Without it (or coerce_graph_type / autofix_edge_ops) graph needs -- and digraph ->
+    pub allow_mismatched_edge_ops: bool,
+}
+
+// Turns every -- into ->, keeping a lint for each edge statement it changed
+struct FixEdgeOps {
+    lints: Vec<Lint>,
+}
+
+impl VisitorMut for FixEdgeOps {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        if !statement.is_edge() {
+            return;
+        }
+        let before = statement.to_string();
+        let Statement::EdgeStmt(edge_stmt) = statement else {
+            return;
+        };
+        let mut fixed = false;
+        let mut rhs: Option<&mut EdgeRhs> = Some(&mut edge_stmt.edge_rhs);
+        while let Some(current) = rhs {
+            if current.edge_op == EdgeOp::UnDirected {
+                current.edge_op = EdgeOp::Directed;
+                fixed = true;
+            }
+            rhs = current.edge_optional.as_deref_mut();
+        }
+        if fixed {
+            self.lints.push(Lint::new(
+                LintKind::FixedEdgeOp,
+                format!("Rewrote -- to -> in `{}`", before),
+            ));
+        }
+    }
+}
+
+fn check_statements_terminated(body: &[ParseBufferItem]) -> Result<()> {
+    let mut rest = body;
+    while let Some(stmt) = parse_stmt(rest) {
+        let consumed = rest.len() - stmt.remaining.len();
+        if stmt.result.is_subgraph() {
+            let open = ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenCurlyBrace));
+            if let Some(start) = rest[..consumed].iter().position(|item| item == &open) {
+                check_statements_terminated(&rest[start + 1..consumed - 1])?;
+            }
+        }
+        rest = &rest[consumed..];
+        match rest.first() {
+            Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon))) => {
+                rest = &rest[1..]
+            }
+            next => bail!(ParserError {
+                token: match next {
+                    Some(ParseBufferItem::Token(token)) => Some(token.clone()),
+                    _ => None,
+                },
+                span: None,
+                reason: Some(format!("Expected ; after `{}`", stmt.result)),
+            }),
+        }
+    }
+    Ok(())
+}
+
+// Errors on the first statement, at any subgraph depth, that is not followed by `;`
+// Subgraphs used as edge ends like `a -> {b c}` group nodes and are not checked
+fn check_semicolons(graph_tokens: &[Token]) -> Result<()> {
+    // the head has no braces, so the body starts after the first one and ends before the last token
+    let Some(open) = graph_tokens
+        .iter()
+        .position(|token| token == &Token::Delimiter(Delimiter::OpenCurlyBrace))
+    else {
+        return Ok(());
     };
-    let _stmt_tokens = &tokens_vec[start_idx..tokens_vec.len()];
-    // dg.statements = parse_stmts::parse_stmts(stmt_tokens);
+    let body: Vec<ParseBufferItem> = graph_tokens[open + 1..graph_tokens.len() - 1]
+        .iter()
+        .cloned()
+        .map(ParseBufferItem::Token)
+        .collect();
+    check_statements_terminated(&body)
+}
+
+// Parse with checks and fixes on top, returning the lints for anything the options fixed
+// Unlike parse, edge operators that do not match the graph type are errors by default
+pub fn parse_with_options(
+    tokens_vec: &[Token],
+    options: &ParseOptions,
+) -> Result<(DotGraph, Vec<Lint>)> {
+    let mut lints = vec![];
+    let mut graph_tokens = tokens_vec;
+    let mut dg = if options.ignore_trailing_tokens {
+        let (dg, rest) = parse_partial(tokens_vec)?;
+        graph_tokens = &tokens_vec[..tokens_vec.len() - rest.len()];
+        if let Some(first) = rest.first() {
+            lints.push(Lint::new(
+                LintKind::TrailingTokens,
+                format!(
+                    "Ignored {} token(s) after the end of the graph, starting at `{}`",
+                    rest.len(),
+                    first.as_str()
+                ),
+            ));
+        }
+        dg
+    } else {
+        parse(tokens_vec)?
+    };
+    if options.require_semicolons {
+        check_semicolons(graph_tokens)?;
+    }
+    if !dg.is_directed() && dg.uses_edge_op(&EdgeOp::Directed) {
+        if options.coerce_graph_type {
+            dg.graph_type = Some(GraphType::Digraph);
+            lints.push(Lint::new(
+                LintKind::CoercedGraphType,
+                "Graph uses -> edges, parsed it as a digraph".to_string(),
+            ));
+        } else if !options.allow_mismatched_edge_ops {
+            bail!(ParserError {
+                token: Some(Token::Delimiter(Delimiter::DirectedEdge)),
+                span: None,
+                reason: Some("Undirected graph uses ->, expected --".to_string()),
+            });
+        }
+    }
+    if dg.is_directed() && dg.uses_edge_op(&EdgeOp::UnDirected) {
+        if options.autofix_edge_ops {
+            let mut fix = FixEdgeOps { lints: vec![] };
+            walk_mut(&mut dg, &mut fix);
+            lints.extend(fix.lints);
+        } else if !options.allow_mismatched_edge_ops {
+            bail!(ParserError {
+                token: Some(Token::Delimiter(Delimiter::UndirectedEdge)),
+                span: None,
+                reason: Some("Digraph uses --, expected ->".to_string()),
+            });
+        }
+    }
+    Ok((dg, lints))
+}
+
+// Parses tokens from lex/tokenize_range, errors in the graph header point at their span
+pub fn parse_spanned(tokens: &[Spanned<Token>]) -> Result<DotGraph> {
+    let (tokens_vec, spans): (Vec<Token>, Vec<Span>) = tokens
+        .iter()
+        .map(|token| (token.value.clone(), token.span.clone()))
+        .unzip();
+    parse_tokens(&tokens_vec, &spans, &ParseLimits::default())
+}
+
+// Index of the first token after the '{' of a graph parsed by parse_head
+fn body_start(dg: &DotGraph) -> usize {
+    match (dg.strict_mode, dg.id.is_some()) {
+        (true, true) => 4,
+        (false, true) => 3,
+        (true, false) => 3,
+        (false, false) => 2,
+    }
+}
+
+fn parse_tokens(tokens_vec: &[Token], spans: &[Span], limits: &ParseLimits) -> Result<DotGraph> {
+    if let Some(max_tokens) = limits.max_tokens {
+        if tokens_vec.len() > max_tokens {
+            return Err(limit_error(
+                tokens_vec.get(max_tokens).cloned(),
+                "tokens",
+                max_tokens,
+            ));
+        }
+    }
+    let mut dg = parser_head::parse_head(tokens_vec, spans)?;
+    check_depth(tokens_vec, limits.max_depth.unwrap_or(DEFAULT_MAX_DEPTH))?;
+    // parse_head has already checked the last token is '}'
+    let statements = parse_statements(&tokens_vec[body_start(&dg)..tokens_vec.len() - 1])?;
+    if let Some(max_statements) = limits.max_statements {
+        if count_statements(&statements) > max_statements {
+            return Err(limit_error(None, "statements", max_statements));
+        }
+    }
+    dg.statements = Some(statements);
 
     Ok(dg)
 }
+
+// A statement parse_lenient left out of the graph, text is the source it was parsed from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedStatement {
+    pub span: Span,
+    pub text: String,
+}
+
+// Tokens start..end of the body end the bad statement starting at `start`: the next ';' outside
+// braces (not included), a '}' closing the braces opened after `start`, or the end of the body
+fn skip_statement(body: &[ParseBufferItem], start: usize) -> usize {
+    let mut depth: usize = 0;
+    let mut end = start;
+    while let Some(ParseBufferItem::Token(token)) = body.get(end) {
+        match token {
+            Token::Delimiter(Delimiter::Semicolon) if depth == 0 => break,
+            Token::Delimiter(Delimiter::OpenCurlyBrace) => depth += 1,
+            Token::Delimiter(Delimiter::ClosedCurlyBrace) => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return end + 1;
+                }
+            }
+            _ => {}
+        }
+        end += 1;
+    }
+    end
+}
+
+// Parses code like parse, but leaves out top level statements that do not parse and reports them
+// When the bad tokens can not start a statement they belong to the statement before, if it was
+// not ended by ';', so `a -> ;` is skipped whole. A subgraph with a bad statement inside is
+// skipped whole
+// Errors in tokenizing and in the graph head still fail the parse
+pub fn parse_lenient(code: &str) -> Result<(DotGraph, Vec<SkippedStatement>)> {
+    let spanned = tokenize_range(code, 0)?;
+    let (tokens_vec, spans): (Vec<Token>, Vec<Span>) = spanned
+        .into_iter()
+        .map(|token| (token.value, token.span))
+        .unzip();
+    let mut dg = parser_head::parse_head(&tokens_vec, &spans)?;
+    check_depth(&tokens_vec, DEFAULT_MAX_DEPTH)?;
+    let offset = body_start(&dg);
+    let body: Vec<ParseBufferItem> = tokens_vec[offset..tokens_vec.len() - 1]
+        .iter()
+        .cloned()
+        .map(ParseBufferItem::Token)
+        .collect();
+    let semicolon = ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon));
+
+    let mut statements = vec![];
+    let mut skipped = vec![];
+    let mut pos = 0;
+    // where the last statement starts, while it is not followed by ';'
+    let mut unterminated: Option<usize> = None;
+    while pos < body.len() {
+        if let Some(stmt) = parse_stmt(&body[pos..]) {
+            let start = pos;
+            pos = body.len() - stmt.remaining.len();
+            statements.push(stmt.result);
+            unterminated = Some(start);
+            if body.get(pos) == Some(&semicolon) {
+                pos += 1;
+                unterminated = None;
+            }
+            continue;
+        }
+        // a stray ';' is not a statement
+        if body[pos] == semicolon {
+            pos += 1;
+            continue;
+        }
+        // statements start with an id, a keyword or '{', anything else continues the one before
+        let continues_previous = matches!(
+            &body[pos],
+            ParseBufferItem::Token(Token::Delimiter(delimiter))
+                if *delimiter != Delimiter::OpenCurlyBrace
+        );
+        let start = match unterminated.take() {
+            Some(start) if continues_previous => {
+                statements.pop();
+                start
+            }
+            _ => pos,
+        };
+        let end = skip_statement(&body, pos);
+        let (first, last) = (&spans[offset + start], &spans[offset + end - 1]);
+        skipped.push(SkippedStatement {
+            span: Span {
+                end: last.end,
+                ..first.clone()
+            },
+            text: code[first.start..last.end].to_string(),
+        });
+        pos = end;
+        if body.get(pos) == Some(&semicolon) {
+            pos += 1;
+        }
+    }
+    dg.statements = Some(statements);
+    Ok((dg, skipped))
+}
+
+// Parses the first graph in `tokens` and returns the tokens after its closing brace
+pub fn parse_partial(tokens: &[Token]) -> Result<(DotGraph, &[Token])> {
+    let mut depth: usize = 0;
+    let mut end = None;
+    for (idx, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Delimiter(Delimiter::OpenCurlyBrace) => depth += 1,
+            Token::Delimiter(Delimiter::ClosedCurlyBrace) if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(idx);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let Some(end) = end else {
+        bail!(ParserError {
+            token: None,
+            span: None,
+            reason: Some("Graph body is not closed".to_string()),
+        });
+    };
+    let dg = parse(&tokens[..=end])?;
+    Ok((dg, &tokens[end + 1..]))
+}
+
+// Parses the tokens of a stmt_list, without the surrounding braces
+fn parse_statements(tokens: &[Token]) -> Result<Vec<Statement>> {
+    let stmt_tokens: Vec<ParseBufferItem> =
+        tokens.iter().cloned().map(ParseBufferItem::Token).collect();
+    let stmt_list = StmtList::default().parse(&stmt_tokens).unwrap();
+
+    if let Some(ParseBufferItem::Token(tkn)) = stmt_list.remaining.first() {
+        bail!(ParserError {
+            token: Some(tkn.clone()),
+            span: None,
+            reason: Some("Could not parse statement".to_string()),
+        });
+    }
+    Ok(stmt_list.result.items)
+}
+
+fn braces_balanced(tokens: &[Token]) -> bool {
+    let mut depth: usize = 0;
+    for token in tokens {
+        match token {
+            Token::Delimiter(Delimiter::OpenCurlyBrace) => depth += 1,
+            Token::Delimiter(Delimiter::ClosedCurlyBrace) => match depth.checked_sub(1) {
+                Some(new_depth) => depth = new_depth,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+impl DotGraph {
+    // Replaces the top level statement at `index` with whatever `new_src` parses to, only new_src
+    // is parsed. Several statements in new_src replace the one statement, an empty new_src
+    // removes it. With unbalanced braces the edit is not a whole statement, so the whole graph
+    // is printed with new_src in its place and parsed again
+    pub fn reparse_statement(&mut self, index: usize, new_src: &str) -> Result<()> {
+        let statements_len = self.statements.as_ref().map_or(0, Vec::len);
+        if index >= statements_len {
+            bail!(ParserError {
+                token: None,
+                span: None,
+                reason: Some(format!(
+                    "No statement at index {}, the graph has {}",
+                    index, statements_len
+                )),
+            });
+        }
+
+        let tokens = tokenize(new_src.to_string())?;
+        if braces_balanced(&tokens) {
+            // the statement sits inside the brace of the graph body
+            check_depth_within(&tokens, 1, DEFAULT_MAX_DEPTH)?;
+            let new_statements = parse_statements(&tokens)?;
+            if let Some(statements) = &mut self.statements {
+                statements.splice(index..index + 1, new_statements);
+            }
+            return Ok(());
+        }
+        let code = self.to_string_replacing_statement(index, new_src);
+        // the balanced path does not check edge operators either
+        let options = ParseOptions {
+            allow_mismatched_edge_ops: true,
+            ..ParseOptions::default()
+        };
+        let (dg, _) = parse_with_options(&tokenize(code)?, &options)?;
+        *self = dg;
+        Ok(())
+    }
+}
+
+// Decodes raw bytes using the graph's `charset` attribute (or fallback_charset when missing) and parses them
+pub fn parse_bytes(bytes: &[u8], fallback_charset: Charset) -> Result<DotGraph> {
+    let charset = detect_charset(bytes).unwrap_or(fallback_charset);
+    let code = charset.decode(bytes)?;
+    let tokens = tokenize(code)?;
+    parse(&tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::Keyword;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_statements() {
+        let tokens =
+            tokenize("digraph G { rankdir=LR; a -> b; b [color=red] }".to_string()).unwrap();
+        let dg = parse(&tokens).unwrap();
+        assert_eq!(dg.statements.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_keywords_ignore_case() {
+        let code = "STRICT Digraph G { SubGraph s { NODE [shape=box]; x } EDGE [color=red] }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert!(dg.strict_mode);
+        assert_eq!(
+            dg.to_string(),
+            "strict digraph G {
+    subgraph s {
+        node [shape=box];
+        x;
+    }
+    edge [color=red];
+}"
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_graph_id() {
+        let code = "digraph \"My Graph\" { a -> b }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.id(), Some("My Graph"));
+        let printed = dg.to_string();
+        assert_eq!(printed, "digraph \"My Graph\" {\n    a -> b;\n}");
+        assert_eq!(parse(&tokenize(printed).unwrap()).unwrap(), dg);
+    }
+
+    #[test]
+    fn test_parse_edge_op_right_after_brace() {
+        for code in [
+            "digraph { {a}->b }",
+            "digraph { { a } -> b }",
+            "digraph{{a}->{b}->c}",
+        ] {
+            let tokens = tokenize(code.to_string()).unwrap();
+            let closing = tokens
+                .iter()
+                .position(|token| *token == Token::Delimiter(Delimiter::ClosedCurlyBrace))
+                .unwrap();
+            assert_eq!(
+                tokens[closing + 1],
+                Token::Delimiter(Delimiter::DirectedEdge),
+                "{}",
+                code
+            );
+            let dg = parse(&tokens).unwrap();
+            assert_eq!(dg.edges()[0].from.id, "a", "{}", code);
+            assert_eq!(dg.edges()[0].to.id, "b", "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_parse_spanned_head_error_position() {
+        let (tokens, errors) = crate::tokenizer::lex("digraph G\n  a -> b }");
+        assert!(errors.is_empty());
+        let err = parse_spanned(&tokens).unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(err.reason.as_deref(), Some("Expected { after graph's name"));
+        assert_eq!(err.token, Some(Token::Identifier("a".to_string())));
+        let span = err.span.as_ref().unwrap();
+        assert_eq!((span.line, span.col, span.start), (1, 3, 12));
+        assert!(err
+            .to_string()
+            .starts_with("Error happened at: line 1, col 3"));
+
+        let (tokens, _) = crate::tokenizer::lex("graph { a -- b ");
+        let err = parse_spanned(&tokens).unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(err.span.as_ref().unwrap().start, 13);
+    }
+
+    #[test]
+    fn test_parse_spanned_matches_parse() {
+        let code = "strict digraph { a -> {b c} [color=red] }";
+        let (tokens, _) = crate::tokenizer::lex(code);
+        assert_eq!(
+            parse_spanned(&tokens).unwrap(),
+            parse(&tokenize(code.to_string()).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_with_options_coerce_graph_type() {
+        let tokens = tokenize("graph { a -> b; b -> c }".to_string()).unwrap();
+        let options = ParseOptions {
+            coerce_graph_type: true,
+            ..ParseOptions::default()
+        };
+        let (dg, lints) = parse_with_options(&tokens, &options).unwrap();
+        assert!(dg.is_directed());
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::CoercedGraphType);
+        assert!(dg.to_string().starts_with("digraph {"));
+    }
+
+    #[test]
+    fn test_parse_with_options_allow_mismatched_edge_ops() {
+        let options = ParseOptions {
+            allow_mismatched_edge_ops: true,
+            ..ParseOptions::default()
+        };
+        for code in [
+            "graph { a -> b }",
+            "digraph { a -- b -> c }",
+            "graph { a -- b }",
+        ] {
+            let tokens = tokenize(code.to_string()).unwrap();
+            let (dg, lints) = parse_with_options(&tokens, &options).unwrap();
+            assert_eq!(dg, parse(&tokens).unwrap());
+            assert!(lints.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_with_options_mismatched_edge_ops_error_by_default() {
+        let options = ParseOptions::default();
+        let tokens = tokenize("graph { a -> b }".to_string()).unwrap();
+        let err = parse_with_options(&tokens, &options).unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(err.token, Some(Token::Delimiter(Delimiter::DirectedEdge)));
+        let tokens = tokenize("digraph { a -- b }".to_string()).unwrap();
+        let err = parse_with_options(&tokens, &options).unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(err.token, Some(Token::Delimiter(Delimiter::UndirectedEdge)));
+
+        let options = ParseOptions {
+            coerce_graph_type: true,
+            ..options
+        };
+        let tokens = tokenize("graph { a -> b }".to_string()).unwrap();
+        assert!(parse_with_options(&tokens, &options)
+            .unwrap()
+            .0
+            .is_directed());
+        let tokens = tokenize("digraph { a -- b }".to_string()).unwrap();
+        assert!(parse_with_options(&tokens, &options).is_err());
+        let tokens = tokenize("graph { a -- b }".to_string()).unwrap();
+        let (dg, lints) = parse_with_options(&tokens, &options).unwrap();
+        assert!(!dg.is_directed());
+        assert!(lints.is_empty());
+    }
+
+    #[test]
+    fn test_parse_with_options_autofix_edge_ops() {
+        let code = "digraph { a -- b -> c; subgraph s { c -- d } d -> a }";
+        let tokens = tokenize(code.to_string()).unwrap();
+        assert!(parse_with_options(&tokens, &ParseOptions::default()).is_err());
+
+        let options = ParseOptions {
+            autofix_edge_ops: true,
+            ..ParseOptions::default()
+        };
+        let (dg, lints) = parse_with_options(&tokens, &options).unwrap();
+        assert_eq!(
+            dg.to_string(),
+            "digraph {
+    a -> b -> c;
+    subgraph s {
+        c -> d;
+    }
+    d -> a;
+}"
+        );
+        let messages: Vec<&str> = lints.iter().map(|lint| lint.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Rewrote -- to -> in `a -- b -> c`",
+                "Rewrote -- to -> in `c -- d`"
+            ]
+        );
+        assert!(lints.iter().all(|lint| lint.kind == LintKind::FixedEdgeOp));
+    }
+
+    #[test]
+    fn test_parse_with_options_ignore_trailing_tokens() {
+        let tokens = tokenize("digraph G { a -> b } xyz".to_string()).unwrap();
+        assert!(parse_with_options(&tokens, &ParseOptions::default()).is_err());
+
+        let options = ParseOptions {
+            ignore_trailing_tokens: true,
+            ..ParseOptions::default()
+        };
+        let (dg, lints) = parse_with_options(&tokens, &options).unwrap();
+        assert_eq!(dg, parse(&tokens[..tokens.len() - 1]).unwrap());
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::TrailingTokens);
+        assert_eq!(
+            lints[0].message,
+            "Ignored 1 token(s) after the end of the graph, starting at `xyz`"
+        );
+
+        let tokens = tokenize("graph { a -- b }".to_string()).unwrap();
+        let (_, lints) = parse_with_options(&tokens, &options).unwrap();
+        assert!(lints.is_empty());
+        let tokens = tokenize("graph { a -- b } }".to_string()).unwrap();
+        let (_, lints) = parse_with_options(&tokens, &options).unwrap();
+        assert_eq!(lints.len(), 1);
+        // a broken graph is still an error
+        let tokens = tokenize("graph { a -- } xyz".to_string()).unwrap();
+        assert!(parse_with_options(&tokens, &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_options_require_semicolons() {
+        let options = ParseOptions {
+            require_semicolons: true,
+            ..ParseOptions::default()
+        };
+        let passing = "digraph { rankdir=LR; a -> {b c}; subgraph s { d; e [x=1]; }; f; }";
+        let tokens = tokenize(passing.to_string()).unwrap();
+        assert!(parse_with_options(&tokens, &options).is_ok());
+        assert!(parse_with_options(&tokens, &ParseOptions::default()).is_ok());
+
+        let failing = [
+            ("digraph { a -> b; c }", "Expected ; after `c`", None),
+            (
+                "digraph { a -> b c; }",
+                "Expected ; after `a -> b`",
+                Some(Token::Identifier("c".to_string())),
+            ),
+            (
+                "digraph { subgraph s { d } ; }",
+                "Expected ; after `d`",
+                None,
+            ),
+            (
+                "digraph { subgraph s { d; } e; }",
+                "Expected ; after `subgraph s {\n    d;\n}`",
+                Some(Token::Identifier("e".to_string())),
+            ),
+        ];
+        for (code, reason, token) in failing {
+            let tokens = tokenize(code.to_string()).unwrap();
+            assert!(parse_with_options(&tokens, &ParseOptions::default()).is_ok());
+            let err = parse_with_options(&tokens, &options).unwrap_err();
+            let err = err.downcast_ref::<ParserError>().unwrap();
+            assert_eq!(err.reason.as_deref(), Some(reason), "{}", code);
+            assert_eq!(err.token, token, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_parse_lenient_skips_bad_statements() {
+        let code = "digraph G {
+    a -> b;
+    c -> ;
+    d [color=red]
+    subgraph s { e -> } f
+    g -> [x=1]; h
+}";
+        assert!(parse(&tokenize(code.to_string()).unwrap()).is_err());
+        let (dg, skipped) = parse_lenient(code).unwrap();
+        assert_eq!(
+            dg.to_string(),
+            "digraph G {
+    a -> b;
+    d [color=red];
+    f;
+    h;
+}"
+        );
+        let texts: Vec<&str> = skipped.iter().map(|skip| skip.text.as_str()).collect();
+        assert_eq!(texts, vec!["c ->", "subgraph s { e -> }", "g -> [x=1]"]);
+        assert_eq!((skipped[0].span.line, skipped[0].span.col), (2, 5));
+        assert_eq!(&code[skipped[1].span.start..skipped[1].span.end], texts[1]);
+
+        let (dg, skipped) = parse_lenient("graph { a -- b; ; c }").unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(dg.node_count(), 3);
+        assert!(parse_lenient("graph { a -- b").is_err());
+        assert!(parse_lenient("graph { a ! b }").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_statement() {
+        let tokens = tokenize("graph { a -- ; }".to_string()).unwrap();
+        assert!(parse(&tokens).is_err());
+    }
+
+    #[test]
+    fn test_parse_degenerate_inputs_error() {
+        let inputs = vec![
+            vec![],
+            vec![Token::Keyword(Keyword::Graph)],
+            vec![
+                Token::Keyword(Keyword::Graph),
+                Token::Delimiter(Delimiter::OpenCurlyBrace),
+                Token::Delimiter(Delimiter::OpenCurlyBrace),
+            ],
+            vec![
+                Token::Keyword(Keyword::Digraph),
+                Token::Identifier("G".to_string()),
+                Token::Delimiter(Delimiter::OpenCurlyBrace),
+            ],
+        ];
+        for tokens in inputs {
+            assert!(parse(&tokens).is_err(), "Expected error for {:?}", tokens);
+        }
+    }
+
+    #[test]
+    fn test_parse_subgraph_lhs_with_attributes() {
+        let tokens = tokenize("digraph { {a b} -> c [style=dashed] }".to_string()).unwrap();
+        let dg = parse(&tokens).unwrap();
+        let edges = dg.edges();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(
+            edges
+                .iter()
+                .map(|edge| (edge.from.id.as_str(), edge.to.id.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("a", "c"), ("b", "c")]
+        );
+        for edge in edges {
+            assert_eq!(
+                edge.attributes,
+                [grammer::Attribute::new(
+                    "style".to_string(),
+                    "dashed".to_string()
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_bytes_latin1() {
+        let bytes = b"graph { charset=\"latin1\"; a [label=\"caf\xe9\"] }";
+        let dg = parse_bytes(bytes, Charset::Utf8).unwrap();
+        let statements = dg.statements.unwrap();
+        let grammer::Statement::NodeStmt(node) = &statements[1] else {
+            panic!("Expected a node statement, got {:?}", statements[1]);
+        };
+        assert_eq!(node.attributes.as_ref().unwrap()[0].rhs, "café");
+    }
+
+    #[test]
+    fn test_parse_bytes_fallback_charset() {
+        let bytes = b"graph { a [label=\"caf\xe9\"] }";
+        assert!(parse_bytes(bytes, Charset::Utf8).is_err());
+        assert!(parse_bytes(bytes, Charset::Latin1).is_ok());
+        assert!(parse_bytes("graph { a [label=\"café\"] }".as_bytes(), Charset::Utf8).is_ok());
+    }
+
+    // Small xorshift generator so the fuzz tests are reproducible without extra dependencies
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+            items[self.below(items.len())]
+        }
+    }
+
+    const FUZZ_IDS: [&str; 9] = [
+        "a", "b2", "_c", "X_y", "\"q x\"", "-1.5", ".5", "7", "\"\\\"\"",
+    ];
+
+    fn random_attr_list(rng: &mut Rng) -> String {
+        let attrs: Vec<String> = (0..rng.below(3) + 1)
+            .map(|_| format!("{}={}", rng.pick(&FUZZ_IDS), rng.pick(&FUZZ_IDS)))
+            .collect();
+        format!("[{}]", attrs.join(", "))
+    }
+
+    fn random_side(rng: &mut Rng, edge_op: &str, depth: usize) -> String {
+        if depth < 2 && rng.below(4) == 0 {
+            format!("{{{}}}", random_stmt_list(rng, edge_op, depth + 1))
+        } else {
+            rng.pick(&FUZZ_IDS).to_string()
+        }
+    }
+
+    fn random_stmt(rng: &mut Rng, edge_op: &str, depth: usize) -> String {
+        match rng.below(6) {
+            0 => format!("{} {}", rng.pick(&FUZZ_IDS), random_attr_list(rng)),
+            1 | 2 => {
+                let mut stmt = random_side(rng, edge_op, depth);
+                for _ in 0..rng.below(3) + 1 {
+                    stmt.push_str(&format!(
+                        " {} {}",
+                        edge_op,
+                        random_side(rng, edge_op, depth)
+                    ));
+                }
+                if rng.below(2) == 0 {
+                    stmt.push_str(&format!(" {}", random_attr_list(rng)));
+                }
+                stmt
+            }
+            3 => format!(
+                "{} {}",
+                rng.pick(&["graph", "node", "edge"]),
+                random_attr_list(rng)
+            ),
+            4 => format!("{}={}", rng.pick(&FUZZ_IDS), rng.pick(&FUZZ_IDS)),
+            _ if depth < 2 => format!(
+                "subgraph {} {{{}}}",
+                rng.pick(&FUZZ_IDS),
+                random_stmt_list(rng, edge_op, depth + 1)
+            ),
+            _ => rng.pick(&FUZZ_IDS).to_string(),
+        }
+    }
+
+    fn random_stmt_list(rng: &mut Rng, edge_op: &str, depth: usize) -> String {
+        let stmts: Vec<String> = (0..rng.below(4))
+            .map(|_| random_stmt(rng, edge_op, depth))
+            .collect();
+        stmts.join("; ")
+    }
+
+    fn random_graph(rng: &mut Rng) -> String {
+        let (graph_type, edge_op) = if rng.below(2) == 0 {
+            ("graph", "--")
+        } else {
+            ("digraph", "->")
+        };
+        let strict = if rng.below(3) == 0 { "strict " } else { "" };
+        let id = if rng.below(2) == 0 {
+            rng.pick(&FUZZ_IDS)
+        } else {
+            ""
+        };
+        format!(
+            "{}{} {} {{ {} }}",
+            strict,
+            graph_type,
+            id,
+            random_stmt_list(rng, edge_op, 0)
+        )
+    }
+
+    #[test]
+    fn test_fuzz_valid_graphs_round_trip() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..100 {
+            let code = random_graph(&mut rng);
+            let dg = parse(&tokenize(code.clone()).unwrap())
+                .unwrap_or_else(|err| panic!("{} failed to parse: {}", code, err));
+            let printed = dg.to_string();
+            let reparsed = parse(&tokenize(printed.clone()).unwrap()).unwrap();
+            assert_eq!(reparsed, dg, "{} printed as {}", code, printed);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_mangled_graphs_do_not_panic() {
+        let noise = [
+            "{", "}", "[", "]", "=", ";", ",", ":", "\"", "-", ">", "--", "->", "/*", "#", "\\",
+            "\n", " ", "a", "é", "subgraph", "node",
+        ];
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..300 {
+            let mut code = random_graph(&mut rng);
+            for _ in 0..rng.below(4) + 1 {
+                let mut idx = rng.below(code.len() + 1);
+                while !code.is_char_boundary(idx) {
+                    idx -= 1;
+                }
+                if rng.below(2) == 0 {
+                    code.insert_str(idx, rng.pick(&noise));
+                } else {
+                    code.truncate(idx);
+                }
+            }
+            // errors are fine, panics are not
+            if let std::result::Result::Ok(tokens) = tokenize(code) {
+                let _ = parse(&tokens);
+            }
+        }
+    }
+
+    fn nested_graph(depth: usize) -> String {
+        format!("graph {{ {}a{} }}", "{".repeat(depth), "}".repeat(depth))
+    }
+
+    #[test]
+    fn test_parse_nesting_within_limit() {
+        let tokens = tokenize(nested_graph(DEFAULT_MAX_DEPTH)).unwrap();
+        assert!(parse(&tokens).is_ok());
+
+        let tokens = tokenize(nested_graph(3)).unwrap();
+        assert!(parse_with_max_depth(&tokens, 3).is_ok());
+    }
+
+    #[test]
+    fn test_parse_nesting_beyond_limit() {
+        let tokens = tokenize(nested_graph(DEFAULT_MAX_DEPTH + 1)).unwrap();
+        let err = parse(&tokens).unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(
+            err.reason.as_deref(),
+            Some("Subgraphs are nested deeper than the limit of 256")
+        );
+
+        let tokens = tokenize(nested_graph(4)).unwrap();
+        assert!(parse_with_max_depth(&tokens, 3).is_err());
+    }
+
+    #[test]
+    fn test_reparse_statement() {
+        let code = "digraph { a [color=red]; b [color=blue]; a -> b }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let before = dg.statements.clone().unwrap();
+        dg.reparse_statement(1, "b [color=green, shape=box]")
+            .unwrap();
+        let after = dg.statements.clone().unwrap();
+        assert_eq!(after.len(), 3);
+        assert_eq!(after[0], before[0]);
+        assert_eq!(after[2], before[2]);
+        assert_eq!(after[1].to_string(), "b [color=green, shape=box]");
+    }
+
+    #[test]
+    fn test_reparse_statement_unbalanced() {
+        let code = "digraph { a; b }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        // the whole graph is parsed again and fails there
+        for new_src in ["subgraph s { a", "a }"] {
+            assert!(dg.reparse_statement(0, new_src).is_err());
+        }
+        // failed edits leave the graph alone
+        assert_eq!(dg.to_string(), "digraph {\n    a;\n    b;\n}");
+        assert!(dg.reparse_statement(2, "c").is_err());
+    }
+
+    #[test]
+    fn test_reparse_statement_nesting_limit() {
+        let mut dg = parse(&tokenize("graph { a; b }".to_string()).unwrap()).unwrap();
+        let nested = |depth: usize| format!("{}a{}", "{".repeat(depth), "}".repeat(depth));
+        let err = dg
+            .reparse_statement(0, &nested(DEFAULT_MAX_DEPTH + 1))
+            .unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(
+            err.reason.as_deref(),
+            Some("Subgraphs are nested deeper than the limit of 256")
+        );
+        // the same limit as parsing the whole graph
+        dg.reparse_statement(0, &nested(DEFAULT_MAX_DEPTH)).unwrap();
+        assert!(parse(&tokenize(dg.to_string()).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_reparse_statement_with_several_statements() {
+        let code = "digraph { a; b }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        dg.reparse_statement(0, "a [x=1]; c -> d; subgraph s { e }")
+            .unwrap();
+        assert_eq!(
+            dg.to_string(),
+            "digraph {\n    a [x=1];\n    c -> d;\n    subgraph s {\n        e;\n    }\n    b;\n}"
+        );
+        dg.reparse_statement(1, "").unwrap();
+        assert_eq!(dg.statements.as_ref().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let tokens = tokenize("digraph A { a -> {b} } graph B { c } trailing".to_string()).unwrap();
+        let (first, rest) = parse_partial(&tokens).unwrap();
+        assert_eq!(first.id(), Some("A"));
+        assert_eq!(first.edges().len(), 1);
+
+        let (second, rest) = parse_partial(rest).unwrap();
+        assert_eq!(second.id(), Some("B"));
+        assert_eq!(rest, &[Token::Identifier("trailing".to_string())]);
+
+        assert!(parse_partial(rest).is_err());
+        let tokens = tokenize("graph { a".to_string()).unwrap();
+        assert!(parse_partial(&tokens).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_limits_default_is_unlimited() {
+        let tokens = tokenize("digraph { a -> b; b -> c; { c; d } }".to_string()).unwrap();
+        assert_eq!(
+            parse_with_limits(&tokens, &ParseLimits::default()).unwrap(),
+            parse(&tokens).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_with_limits_max_statements() {
+        let tokens = tokenize("digraph { a -> b; b -> {c; d} }".to_string()).unwrap();
+        let limits = ParseLimits {
+            max_statements: Some(4),
+            ..ParseLimits::default()
+        };
+        assert!(parse_with_limits(&tokens, &limits).is_ok());
+
+        let limits = ParseLimits {
+            max_statements: Some(3),
+            ..ParseLimits::default()
+        };
+        let err = parse_with_limits(&tokens, &limits).unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(
+            err.reason.as_deref(),
+            Some("Graph has more statements than the limit of 3")
+        );
+    }
+
+    #[test]
+    fn test_parse_with_limits_max_tokens() {
+        let tokens = tokenize("graph { a -- b }".to_string()).unwrap();
+        let limits = ParseLimits {
+            max_tokens: Some(5),
+            ..ParseLimits::default()
+        };
+        let err = parse_with_limits(&tokens, &limits).unwrap_err();
+        let err = err.downcast_ref::<ParserError>().unwrap();
+        assert_eq!(
+            err.token,
+            Some(Token::Delimiter(Delimiter::ClosedCurlyBrace))
+        );
+    }
+
+    #[test]
+    fn test_parse_with_limits_max_depth() {
+        let tokens = tokenize("graph { { { a } } }".to_string()).unwrap();
+        let limits = ParseLimits {
+            max_depth: Some(1),
+            ..ParseLimits::default()
+        };
+        assert!(parse_with_limits(&tokens, &limits).is_err());
+    }
+}
@@ -1,34 +1,17 @@
 use crate::tokenizer::Token;
 
-use super::{parser_compass::Compass, parser_port::Port};
-
-#[derive(Clone, Debug, PartialEq)]
-pub enum ParseOutput {
-    Compass(Compass),
-    Port(Port),
-}
-
 #[derive(Clone, Debug, PartialEq)]
 pub enum ParseBufferItem {
     Token(Token),
-    ParseOutput(ParseOutput),
 }
 
+// remaining borrows from the parser input, so parsing a statement never copies the tokens after it
 #[derive(Clone, Debug, PartialEq)]
-pub struct ParseResult<T> {
+pub struct ParseResult<'a, T> {
     pub result: T,
-    pub remaining: Vec<ParseBufferItem>,
+    pub remaining: &'a [ParseBufferItem],
 }
 
 pub trait Parser<T> {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<ParseResult<T>>;
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, T>>;
 }
-
-// pub struct ParseResult<'a, T> {
-//     pub result: T,
-//     pub remaining: &'a [Token],
-// }
-
-// pub trait Parser<'a, T> {
-//     fn parse(&self, input: &'a [Token]) -> Option<ParseResult<'a, T>>;
-// }
@@ -5,73 +5,36 @@ use super::{
     parser_attribute::Attribute,
 };
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct AList {
     pub items: Vec<Attribute>,
 }
 
-impl Default for AList {
-    fn default() -> Self {
-        AList { items: vec![] }
-    }
-}
-
 // I am taking a risk here, ID = ID is same as Attribute
 // a_list : ID '=' ID [ (';' | ',') ] [ a_list ]
 impl Parser<AList> for AList {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<ParseResult<AList>> {
-        if input.len() < 3 {
-            return None;
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, AList>> {
+        // Collected in a loop, recursing and concatenating copied the list once per attribute
+        let mut items: Vec<Attribute> = vec![];
+        let mut rest = input;
+        while let Some(attribute) = Attribute::default().parse(rest) {
+            items.push(attribute.result);
+            // the separator is optional, [a=1 b=2] lists two attributes
+            rest = match attribute.remaining.first() {
+                Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon)))
+                | Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Comma))) => {
+                    &attribute.remaining[1..]
+                }
+                _ => attribute.remaining,
+            };
         }
-        let attribute: Option<ParseResult<Attribute>> = Attribute::default().parse(&input[0..3].to_vec());
-
-        if attribute.is_none() {
+        if items.is_empty() {
             return None;
         }
-
-        let results = attribute.unwrap();
-        let attributes = vec![results.result];
-
-        let mut has_more = false;
-        match input.get(3) {
-            Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon))) => {
-                has_more = true;
-            }
-            Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Comma))) => {
-                has_more = true;
-            }
-            _ => {}
-        };
-
-        if !has_more {
-            return Some(ParseResult {
-                result: AList {
-                    items: attributes,
-                },
-                remaining: input[3..].to_vec(),
-            });
-        }
-
-        let rest = &input[4..];
-        let next = AList::default().parse(rest);
-        match next {
-            None => Some(ParseResult {
-                result: AList {
-                    items: attributes,
-                },
-                remaining: rest.to_vec(),
-            }),
-            Some(next) => {
-                let next_items = next.result.items;
-                let items = [attributes, next_items].concat();
-                return Some(ParseResult {
-                    result: AList {
-                        items,
-                    },
-                    remaining: next.remaining,
-                });
-            }
-        }
+        Some(ParseResult {
+            result: AList { items },
+            remaining: rest,
+        })
     }
 }
 
@@ -95,10 +58,12 @@ mod tests {
                 Attribute {
                     lhs: "node1".to_string(),
                     rhs: "node2".to_string(),
+                    quoted_lhs: false,
                 },
                 Attribute {
                     lhs: "node3".to_string(),
                     rhs: "node4".to_string(),
+                    quoted_lhs: false,
                 },
             ],
         };
@@ -107,7 +72,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![]
+                remaining: &[]
             })
         );
     }
@@ -133,14 +98,17 @@ mod tests {
                 Attribute {
                     lhs: "node1".to_string(),
                     rhs: "node2".to_string(),
+                    quoted_lhs: false,
                 },
                 Attribute {
                     lhs: "node3".to_string(),
                     rhs: "node4".to_string(),
+                    quoted_lhs: false,
                 },
                 Attribute {
                     lhs: "node5".to_string(),
                     rhs: "node6".to_string(),
+                    quoted_lhs: false,
                 },
             ],
         };
@@ -149,10 +117,68 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![ParseBufferItem::Token(Token::Identifier(
+                remaining: &[ParseBufferItem::Token(Token::Identifier(
                     "node7".to_string()
                 ))]
             })
         );
     }
+
+    #[test]
+    fn test_parse_a_list_with_comments() {
+        let codes = ["graph { a [x=1, /* c */ y=2] }", "graph { a [x=1 /* c */; y=2] }"];
+        for code in codes {
+            let tokens = crate::tokenizer::tokenize(code.to_string()).unwrap();
+            let input: Vec<ParseBufferItem> =
+                tokens[4..].iter().cloned().map(ParseBufferItem::Token).collect();
+            let result = AList::default().parse(&input).unwrap();
+            assert_eq!(
+                result.result.items,
+                vec![
+                    Attribute::new("x".to_string(), "1".to_string()),
+                    Attribute::new("y".to_string(), "2".to_string()),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_a_list_without_separators() {
+        let tokens = crate::tokenizer::tokenize("color=red shape=box\nx=1, y=2 ]".to_string()).unwrap();
+        let input: Vec<ParseBufferItem> = tokens.into_iter().map(ParseBufferItem::Token).collect();
+        let result = AList::default().parse(&input).unwrap();
+        assert_eq!(
+            result.result.items,
+            vec![
+                Attribute::new("color".to_string(), "red".to_string()),
+                Attribute::new("shape".to_string(), "box".to_string()),
+                Attribute::new("x".to_string(), "1".to_string()),
+                Attribute::new("y".to_string(), "2".to_string()),
+            ]
+        );
+        assert_eq!(
+            result.remaining,
+            vec![ParseBufferItem::Token(Token::Delimiter(Delimiter::ClosedSquareBrace))]
+        );
+    }
+
+    #[test]
+    fn test_parse_a_list_trailing_separator() {
+        for code in ["a=1,]", "a=1;]", "a=1,"] {
+            let tokens = crate::tokenizer::tokenize(code.to_string()).unwrap();
+            let input: Vec<ParseBufferItem> = tokens.into_iter().map(ParseBufferItem::Token).collect();
+            let result = AList::default().parse(&input).unwrap();
+            assert_eq!(result.result.items, vec![Attribute::new("a".to_string(), "1".to_string())]);
+            assert_eq!(result.remaining, &input[4..]);
+        }
+
+        let parse = |code: &str| {
+            crate::parser::parse(&crate::tokenizer::tokenize(code.to_string()).unwrap()).unwrap()
+        };
+        let with_trailing = ["graph { x [a=1,] }", "graph { x [a=1;] }", "graph { x -- y [a=1, b=2,] }"];
+        let without = ["graph { x [a=1] }", "graph { x [a=1] }", "graph { x -- y [a=1, b=2] }"];
+        for (code, expected) in with_trailing.into_iter().zip(without) {
+            assert_eq!(parse(code), parse(expected), "{}", code);
+        }
+    }
 }
@@ -6,20 +6,14 @@ use super::{
     parser_attribute::Attribute,
 };
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct AttrList {
     pub items: Vec<Attribute>,
 }
 
-impl Default for AttrList {
-    fn default() -> Self {
-        AttrList { items: vec![] }
-    }
-}
-
 // attr_list : '[' [ a_list ] ']' [ attr_list ]
 impl Parser<AttrList> for AttrList {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<ParseResult<AttrList>> {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, AttrList>> {
         if input.len() < 5 {
             return None;
         }
@@ -31,23 +25,9 @@ impl Parser<AttrList> for AttrList {
         }
 
         // check if the next item is a_list
-        let a_list = AList::default().parse(&input[1..].to_vec());
-        let mut items: Vec<Attribute> = vec![];
-
-        if a_list.is_none() {
-            return None;
-        }
-
-
-        if let Some(a_list) = a_list.clone() {
-            items = [items, a_list.result.items].concat();
-        }
-
-        let rest = a_list?.remaining;
-
-        if rest.is_empty() {
-            return None;
-        }
+        let a_list = AList::default().parse(&input[1..])?;
+        let mut items: Vec<Attribute> = a_list.result.items;
+        let rest = a_list.remaining;
 
         if rest.first()?
             != &ParseBufferItem::Token(Token::Delimiter(Delimiter::ClosedSquareBrace))
@@ -57,17 +37,17 @@ impl Parser<AttrList> for AttrList {
 
         let rest = &rest[1..];
 
-        let next = AttrList::default().parse(&rest);
+        let next = AttrList::default().parse(rest);
 
         if next.is_none() {
             return Some(ParseResult {
                 result: AttrList { items },
-                remaining: rest.to_vec(),
+                remaining: rest,
             });
         }
 
         let next = next.unwrap();
-        items = [items, next.result.items].concat();
+        items.extend(next.result.items);
 
         Some(ParseResult {
             result: AttrList { items },
@@ -94,12 +74,13 @@ mod tests {
             items: vec![Attribute {
                 lhs: "label".to_string(),
                 rhs: "hello".to_string(),
+                quoted_lhs: false,
             }],
         };
 
         let result = AttrList::default().parse(&input);
 
-        assert_eq!(result.is_some(), true);
+        assert!(result.is_some());
         assert_eq!(result.unwrap().result, expected);
     }
 
@@ -123,17 +104,19 @@ mod tests {
                 Attribute {
                     lhs: "label".to_string(),
                     rhs: "hello".to_string(),
+                    quoted_lhs: false,
                 },
                 Attribute {
                     lhs: "color".to_string(),
                     rhs: "red".to_string(),
+                    quoted_lhs: false,
                 },
             ],
         };
 
         let result = AttrList::default().parse(&input);
 
-        assert_eq!(result.is_some(), true);
+        assert!(result.is_some());
         assert_eq!(result.unwrap().result, expected);
     }
 
@@ -158,17 +141,19 @@ mod tests {
                 Attribute {
                     lhs: "label".to_string(),
                     rhs: "hello".to_string(),
+                    quoted_lhs: false,
                 },
                 Attribute {
                     lhs: "color".to_string(),
                     rhs: "red".to_string(),
+                    quoted_lhs: false,
                 },
             ],
         };
 
         let result = AttrList::default().parse(&input);
 
-        assert_eq!(result.is_some(), true);
+        assert!(result.is_some());
         assert_eq!(result.clone().unwrap().result, expected);
         assert_eq!(result.clone().unwrap().remaining.len(), 1);
     }
@@ -7,11 +7,27 @@ use super::parser::{ParseBufferItem, ParseResult, Parser};
 pub struct Attribute {
     pub lhs: String,
     pub rhs: String,
+    // the key was written quoted, like "color"=red, and is printed back the same way
+    pub quoted_lhs: bool,
 }
 
 impl Attribute {
     pub fn new(lhs: String, rhs: String) -> Self {
-        Self { lhs, rhs }
+        Self {
+            lhs,
+            rhs,
+            quoted_lhs: false,
+        }
+    }
+
+    // Boolean spellings graphviz accepts: true/false, yes/no in any case and integers,
+    // where 0 is false and any other number true
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.rhs.to_lowercase().as_str() {
+            "true" | "yes" => Some(true),
+            "false" | "no" => Some(false),
+            value => value.parse::<i64>().ok().map(|number| number != 0),
+        }
     }
 }
 
@@ -20,24 +36,35 @@ impl Default for Attribute {
         Attribute {
             lhs: "".to_string(),
             rhs: "".to_string(),
+            quoted_lhs: false,
         }
     }
 }
 
 
 impl Parser<Attribute> for Attribute {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<ParseResult<Attribute>> {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, Attribute>> {
         let first: Option<&ParseBufferItem> = input.first();
         let second: Option<&ParseBufferItem> = input.get(1);
         let third: Option<&ParseBufferItem> = input.get(2);
-        match (first, second, third) {
-            (
-                Some(ParseBufferItem::Token(Token::Identifier(lhs))),
-                Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Equal))),
-                Some(ParseBufferItem::Token(Token::Identifier(rhs))),
-            ) => Some(ParseResult {
-                result: Attribute::new(lhs.to_string(), rhs.to_string()),
-                remaining: input[3..].to_vec(),
+        // values are not keyword sensitive, the tokenizer already reads `x=node` as the id "node"
+        let rhs = match third {
+            Some(ParseBufferItem::Token(Token::Identifier(rhs))) => rhs.as_str(),
+            _ => return None,
+        };
+        let (lhs, quoted_lhs) = match first {
+            Some(ParseBufferItem::Token(Token::Identifier(lhs))) => (lhs, false),
+            Some(ParseBufferItem::Token(Token::QuotedKey(lhs))) => (lhs, true),
+            _ => return None,
+        };
+        match second {
+            Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Equal))) => Some(ParseResult {
+                result: Attribute {
+                    lhs: lhs.to_string(),
+                    rhs: rhs.to_string(),
+                    quoted_lhs,
+                },
+                remaining: &input[3..],
             }),
             _ => None,
         }
@@ -46,6 +73,8 @@ impl Parser<Attribute> for Attribute {
 
 #[cfg(test)]
 mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
     use super::*;
 
     #[test]
@@ -57,7 +86,7 @@ mod tests {
         ];
         let expected = Attribute::new("label".to_string(), "hello".to_string());
         let result = Attribute::new("".to_string(), "".to_string()).parse(&input);
-        assert_eq!(result, Some(ParseResult { result: expected, remaining: vec![] }));
+        assert_eq!(result, Some(ParseResult { result: expected, remaining: &[] }));
     }
 
     #[test]
@@ -70,11 +99,21 @@ mod tests {
         ];
         let expected = Attribute::new("label".to_string(), "hello".to_string());
         let result = Attribute::new("".to_string(), "".to_string()).parse(&input);
-        assert_eq!(result, Some(ParseResult { result: expected, remaining: vec![ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon))] }));
+        assert_eq!(result, Some(ParseResult { result: expected, remaining: &[ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon))] }));
     }
 
 
 
+    #[test]
+    fn test_parse_attribute_quoted_key() {
+        let tokens = crate::tokenizer::tokenize("\"a b\"=1".to_string()).unwrap();
+        let input: Vec<ParseBufferItem> = tokens.into_iter().map(ParseBufferItem::Token).collect();
+        let mut expected = Attribute::new("a b".to_string(), "1".to_string());
+        expected.quoted_lhs = true;
+        let result = Attribute::default().parse(&input);
+        assert_eq!(result, Some(ParseResult { result: expected, remaining: &[] }));
+    }
+
     #[test]
     fn test_parse_attribute_fail() {
         let input = vec![
@@ -84,4 +123,30 @@ mod tests {
         let result = Attribute::new("".to_string(), "".to_string()).parse(&input);
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn test_attribute_as_bool() {
+        let value = |rhs: &str| Attribute::new("constraint".to_string(), rhs.to_string()).as_bool();
+        for rhs in ["true", "TRUE", "yes", "Yes", "1", "2"] {
+            assert_eq!(value(rhs), Some(true), "{}", rhs);
+        }
+        for rhs in ["false", "False", "no", "NO", "0"] {
+            assert_eq!(value(rhs), Some(false), "{}", rhs);
+        }
+        for rhs in ["", "maybe", "1.5", "truthy"] {
+            assert_eq!(value(rhs), None, "{}", rhs);
+        }
+    }
+
+    #[test]
+    fn test_parse_keyword_values_in_graph() {
+        let code = "digraph { x=node; y = Edge; a [x=edge]; a -> b [x=Graph, label=Node] }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.graph_attrs()["x"], "node");
+        assert_eq!(dg.graph_attrs()["y"], "Edge");
+        assert_eq!(
+            dg.to_string(),
+            "digraph {\n    x=\"node\";\n    y=\"Edge\";\n    a [x=\"edge\"];\n    a -> b [x=\"Graph\", label=\"Node\"];\n}"
+        );
+    }
 }
@@ -35,31 +35,28 @@ impl Default for AttrStmt {
 }
 
 impl Parser<AttrStmt> for AttrStmt {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<ParseResult<AttrStmt>> {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, AttrStmt>> {
         if input.is_empty() {
             return None;
         }
         let first: Option<&ParseBufferItem> = input.first();
         match first {
             Some(ParseBufferItem::Token(Token::Keyword(Keyword::Graph))) => {
-                let attr_list = AttrList::default().parse(&input[1..]);
-                let attr_list = attr_list.as_ref()?.clone();
+                let attr_list = AttrList::default().parse(&input[1..])?;
                 Some(ParseResult {
                     result: AttrStmt::new(AttrStmtKind::Graph, attr_list.result),
                     remaining: attr_list.remaining,
                 })
             }
             Some(ParseBufferItem::Token(Token::Keyword(Keyword::Node))) => {
-                let attr_list = AttrList::default().parse(&input[1..]);
-                let attr_list = attr_list.as_ref()?.clone();
+                let attr_list = AttrList::default().parse(&input[1..])?;
                 Some(ParseResult {
                     result: AttrStmt::new(AttrStmtKind::Node, attr_list.result),
                     remaining: attr_list.remaining,
                 })
             }
             Some(ParseBufferItem::Token(Token::Keyword(Keyword::Edge))) => {
-                let attr_list = AttrList::default().parse(&input[1..]);
-                let attr_list = attr_list.as_ref()?.clone();
+                let attr_list = AttrList::default().parse(&input[1..])?;
                 Some(ParseResult {
                     result: AttrStmt::new(AttrStmtKind::Edge, attr_list.result),
                     remaining: attr_list.remaining,
@@ -93,6 +90,7 @@ mod tests {
                 items: vec![parser_attribute::Attribute {
                     lhs: "label".to_string(),
                     rhs: "hello".to_string(),
+                    quoted_lhs: false,
                 }],
             },
         );
@@ -101,7 +99,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![]
+                remaining: &[]
             })
         );
     }
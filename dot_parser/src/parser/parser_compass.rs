@@ -18,13 +18,8 @@ pub enum Compass {
 }
 
 impl Parser<Compass> for Compass {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<ParseResult<Compass>> {
-        let first = match input.first()? {
-            ParseBufferItem::Token(val) => val,
-            _ => {
-                return None;
-            }
-        };
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, Compass>> {
+        let ParseBufferItem::Token(first) = input.first()?;
 
         match first {
             Token::Identifier(ref val) => {
@@ -43,7 +38,7 @@ impl Parser<Compass> for Compass {
                 };
                 result.map(|compass| ParseResult {
                     result: compass,
-                    remaining: input[1..].to_vec(),
+                    remaining: &input[1..],
                 })
             }
             _ => None,
@@ -64,7 +59,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![]
+                remaining: &[]
             })
         );
     }
@@ -81,7 +76,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![ParseBufferItem::Token(Token::Identifier("ne".to_string()))]
+                remaining: &[ParseBufferItem::Token(Token::Identifier("ne".to_string()))]
             })
         );
     }
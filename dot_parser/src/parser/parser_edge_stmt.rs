@@ -0,0 +1,189 @@
+use crate::tokenizer::{Delimiter, Token};
+
+use super::{
+    grammer::{EdgeOp, EdgeRhs, EdgeStmt, EdgeStmtSide, SubGraph},
+    parser::{ParseBufferItem, ParseResult, Parser},
+    parser_attr_list::AttrList,
+    parser_node_id::NodeId,
+};
+
+// edgeop : '->' | '--'
+impl Parser<EdgeOp> for EdgeOp {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, EdgeOp>> {
+        let edge_op = match input.first()? {
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::DirectedEdge)) => EdgeOp::Directed,
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::UndirectedEdge)) => {
+                EdgeOp::UnDirected
+            }
+            _ => return None,
+        };
+        Some(ParseResult {
+            result: edge_op,
+            remaining: &input[1..],
+        })
+    }
+}
+
+// (node_id | subgraph)
+impl Parser<EdgeStmtSide> for EdgeStmtSide {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, EdgeStmtSide>> {
+        if let Some(subgraph) = SubGraph::default().parse(input) {
+            return Some(ParseResult {
+                result: EdgeStmtSide::SubGraph(subgraph.result),
+                remaining: subgraph.remaining,
+            });
+        }
+        let node_id = NodeId::default().parse(input)?;
+        Some(ParseResult {
+            result: EdgeStmtSide::NodeId(node_id.result),
+            remaining: node_id.remaining,
+        })
+    }
+}
+
+// edgeRHS : edgeop (node_id | subgraph) [ edgeRHS ]
+impl Parser<EdgeRhs> for EdgeRhs {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, EdgeRhs>> {
+        // Collect the chain in a loop instead of recursing, long chains would grow the stack
+        let mut links: Vec<(EdgeOp, EdgeStmtSide)> = vec![];
+        let mut remaining = input;
+        while let Some(edge_op) = EdgeOp::default().parse(remaining) {
+            let Some(edge_to) = EdgeStmtSide::default().parse(edge_op.remaining) else {
+                break;
+            };
+            links.push((edge_op.result, edge_to.result));
+            remaining = edge_to.remaining;
+        }
+
+        let mut edge_rhs: Option<EdgeRhs> = None;
+        for (edge_op, edge_to) in links.into_iter().rev() {
+            edge_rhs = Some(EdgeRhs {
+                edge_op,
+                edge_to,
+                edge_optional: edge_rhs.map(Box::new),
+            });
+        }
+        Some(ParseResult {
+            result: edge_rhs?,
+            remaining,
+        })
+    }
+}
+
+// The part of an edge_stmt after its lhs: edgeRHS [ attr_list ]
+pub(super) fn parse_edge_stmt_rest<'a>(
+    edge_lhs: EdgeStmtSide,
+    input: &'a [ParseBufferItem],
+) -> Option<ParseResult<'a, EdgeStmt>> {
+    let edge_rhs = EdgeRhs::default().parse(input)?;
+    let attr_list = AttrList::default().parse(edge_rhs.remaining);
+    match attr_list {
+        None => Some(ParseResult {
+            result: EdgeStmt {
+                edge_lhs,
+                edge_rhs: edge_rhs.result,
+                attributes: None,
+            },
+            remaining: edge_rhs.remaining,
+        }),
+        Some(attr_list) => Some(ParseResult {
+            result: EdgeStmt {
+                edge_lhs,
+                edge_rhs: edge_rhs.result,
+                attributes: Some(attr_list.result.items),
+            },
+            remaining: attr_list.remaining,
+        }),
+    }
+}
+
+// edge_stmt : (node_id | subgraph) edgeRHS [ attr_list ]
+impl Parser<EdgeStmt> for EdgeStmt {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, EdgeStmt>> {
+        let edge_lhs = EdgeStmtSide::default().parse(input)?;
+        parse_edge_stmt_rest(edge_lhs.result, edge_lhs.remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> EdgeStmtSide {
+        EdgeStmtSide::NodeId(NodeId {
+            id: id.to_string(),
+            port: None,
+        })
+    }
+
+    #[test]
+    fn test_parse_edge_stmt() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::DirectedEdge)),
+            ParseBufferItem::Token(Token::Identifier("b".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon)),
+        ];
+        let expected = EdgeStmt {
+            edge_lhs: node("a"),
+            edge_rhs: EdgeRhs {
+                edge_op: EdgeOp::Directed,
+                edge_to: node("b"),
+                edge_optional: None,
+            },
+            attributes: None,
+        };
+        let result = EdgeStmt::default().parse(&input);
+        assert_eq!(
+            result,
+            Some(ParseResult {
+                result: expected,
+                remaining: &[ParseBufferItem::Token(Token::Delimiter(
+                    Delimiter::Semicolon
+                ))]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_edge_stmt_chain() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::UndirectedEdge)),
+            ParseBufferItem::Token(Token::Identifier("b".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::UndirectedEdge)),
+            ParseBufferItem::Token(Token::Identifier("c".to_string())),
+        ];
+        let expected = EdgeStmt {
+            edge_lhs: node("a"),
+            edge_rhs: EdgeRhs {
+                edge_op: EdgeOp::UnDirected,
+                edge_to: node("b"),
+                edge_optional: Some(Box::new(EdgeRhs {
+                    edge_op: EdgeOp::UnDirected,
+                    edge_to: node("c"),
+                    edge_optional: None,
+                })),
+            },
+            attributes: None,
+        };
+        let result = EdgeStmt::default().parse(&input);
+        assert_eq!(
+            result,
+            Some(ParseResult {
+                result: expected,
+                remaining: &[]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_edge_stmt_fail_without_rhs() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon)),
+        ];
+        let result = EdgeStmt::default().parse(&input);
+        assert_eq!(result, None);
+    }
+}
@@ -2,13 +2,41 @@ use anyhow::{bail, Result};
 
 use crate::{
     parser::grammer::ParserError,
-    tokenizer::{Delimiter, Keyword, Token},
+    tokenizer::{Delimiter, Keyword, Span, Token},
 };
 
 use super::grammer::{DotGraph, GraphType};
 
+// Walks the head tokens, remembering the index of the last one so errors can point at its span
+struct HeadTokens<'a> {
+    tokens: &'a [Token],
+    spans: &'a [Span],
+    next: usize,
+}
+
+impl<'a> HeadTokens<'a> {
+    fn error(&self, idx: usize, token: Option<&Token>, reason: &str) -> anyhow::Error {
+        anyhow::anyhow!(ParserError {
+            token: token.cloned(),
+            span: self.spans.get(idx).cloned(),
+            reason: Some(reason.to_string()),
+        })
+    }
+
+    // Tokens are borrowed, only the graph's id and the token in an error get cloned
+    fn next_token(&mut self) -> Result<(usize, &'a Token)> {
+        let idx = self.next;
+        let Some(tkn) = self.tokens.get(idx) else {
+            return Err(self.error(idx, None, "Unexpected end of graph header"));
+        };
+        self.next += 1;
+        Ok((idx, tkn))
+    }
+}
+
 // This one is not parser-combinator for now.. But, I could have ~~
-pub fn parse_head(tokens_vec: &[Token]) -> Result<DotGraph> {
+// Errors carry the span of their token when `spans` has one
+pub fn parse_head(tokens_vec: &[Token], spans: &[Span]) -> Result<DotGraph> {
     let mut dg = DotGraph {
         graph_type: None,
         strict_mode: false,
@@ -19,16 +47,21 @@ pub fn parse_head(tokens_vec: &[Token]) -> Result<DotGraph> {
     if tokens_vec.len() < 3 {
         bail!(ParserError {
             token: None,
+            span: None,
             reason: Some("Need atleast 3 tokens".to_string()),
         });
     }
 
-    let mut tokens = tokens_vec.iter();
+    let mut tokens = HeadTokens {
+        tokens: tokens_vec,
+        spans,
+        next: 0,
+    };
 
-    let mut tkn = tokens.next().unwrap().clone();
-    if tkn == Token::Keyword(Keyword::Strict) {
+    let (mut idx, mut tkn) = tokens.next_token()?;
+    if tkn == &Token::Keyword(Keyword::Strict) {
         dg.strict_mode = true;
-        tkn = tokens.next().unwrap().clone();
+        (idx, tkn) = tokens.next_token()?;
     }
     match tkn {
         Token::Keyword(Keyword::Graph) => {
@@ -38,45 +71,103 @@ pub fn parse_head(tokens_vec: &[Token]) -> Result<DotGraph> {
             dg.graph_type = Some(GraphType::Digraph);
         }
         _ => {
-            bail!(ParserError {
-                token: Some(tkn),
-                reason: Some("Grpah should start with Keywords: strict/graph/digraph".to_string()),
-            });
+            return Err(tokens.error(
+                idx,
+                Some(tkn),
+                "Grpah should start with Keywords: strict/graph/digraph",
+            ));
         }
     }
 
-    tkn = tokens.next().unwrap().clone();
+    (idx, tkn) = tokens.next_token()?;
     match tkn {
         Token::Identifier(id) => {
-            dg.id = Some(id);
-            tkn = tokens.next().unwrap().clone();
-            if tkn != Token::Delimiter(Delimiter::OpenCurlyBrace) {
-                bail!(ParserError {
-                    token: Some(tkn),
-                    reason: Some("Expected { after graph's name".to_string()),
-                });
+            dg.id = Some(id.clone());
+            (idx, tkn) = tokens.next_token()?;
+            if tkn != &Token::Delimiter(Delimiter::OpenCurlyBrace) {
+                return Err(tokens.error(idx, Some(tkn), "Expected { after graph's name"));
             }
         }
         Token::Delimiter(Delimiter::OpenCurlyBrace) => {
             dg.id = None;
         }
         _ => {
-            bail!(ParserError {
-                token: Some(tkn),
-                reason: Some(
-                    "After graph/digraph, we expect graph's name or open brace".to_string()
-                ),
-            });
+            return Err(tokens.error(
+                idx,
+                Some(tkn),
+                "After graph/digraph, we expect graph's name or open brace",
+            ));
         }
     }
 
-    let last = tokens.last().unwrap().clone();
-    if last != Token::Delimiter(Delimiter::ClosedCurlyBrace) {
-        bail!(ParserError {
-            token: Some(last),
-            reason: Some("Expected } at the end".to_string())
-        });
+    // the closing brace must not be the opening one we just consumed
+    let last_idx = tokens_vec.len() - 1;
+    let last = tokens_vec.get(last_idx).filter(|_| last_idx > idx);
+    if last != Some(&Token::Delimiter(Delimiter::ClosedCurlyBrace)) {
+        return Err(tokens.error(last_idx, last, "Expected } at the end"));
     }
 
     Ok(dg)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_head() {
+        let tokens = vec![
+            Token::Keyword(Keyword::Strict),
+            Token::Keyword(Keyword::Digraph),
+            Token::Identifier("G".to_string()),
+            Token::Delimiter(Delimiter::OpenCurlyBrace),
+            Token::Delimiter(Delimiter::ClosedCurlyBrace),
+        ];
+        let dg = parse_head(&tokens, &[]).unwrap();
+        assert!(dg.strict_mode);
+        assert_eq!(dg.graph_type, Some(GraphType::Digraph));
+        assert_eq!(dg.id, Some("G".to_string()));
+    }
+
+    #[test]
+    fn test_parse_head_keywords_ignore_case() {
+        let tokens = crate::tokenizer::tokenize("STRICT Digraph G { }".to_string()).unwrap();
+        let dg = parse_head(&tokens, &[]).unwrap();
+        assert!(dg.strict_mode);
+        assert_eq!(dg.graph_type, Some(GraphType::Digraph));
+        assert_eq!(dg.id, Some("G".to_string()));
+    }
+
+    #[test]
+    fn test_parse_head_truncated_inputs_error() {
+        let inputs = vec![
+            vec![
+                Token::Keyword(Keyword::Strict),
+                Token::Keyword(Keyword::Graph),
+                Token::Delimiter(Delimiter::OpenCurlyBrace),
+            ],
+            vec![
+                Token::Keyword(Keyword::Strict),
+                Token::Keyword(Keyword::Graph),
+                Token::Identifier("G".to_string()),
+            ],
+            vec![
+                Token::Keyword(Keyword::Graph),
+                Token::Identifier("G".to_string()),
+                Token::Delimiter(Delimiter::OpenCurlyBrace),
+            ],
+            vec![
+                Token::Keyword(Keyword::Strict),
+                Token::Keyword(Keyword::Strict),
+                Token::Keyword(Keyword::Strict),
+            ],
+        ];
+        for tokens in inputs {
+            assert!(
+                parse_head(&tokens, &[]).is_err(),
+                "Expected error for {:?}",
+                tokens
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::tokenizer::{Delimiter, Token};
+use crate::tokenizer::Token;
 
 use super::{
     parser::{ParseBufferItem, ParseResult, Parser},
@@ -21,7 +21,7 @@ impl Default for NodeId {
 }
 
 impl Parser<NodeId> for NodeId {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<ParseResult<NodeId>> {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, NodeId>> {
         let first: &ParseBufferItem = input.first()?;
         // first item should be an identifier
 
@@ -36,7 +36,7 @@ impl Parser<NodeId> for NodeId {
         match is_port {
             None => Some(ParseResult {
                 result: NodeId { id, port: None },
-                remaining: rest.to_vec(),
+                remaining: rest,
             }),
             Some(port) => Some(ParseResult {
                 result: NodeId {
@@ -51,6 +51,8 @@ impl Parser<NodeId> for NodeId {
 
 #[cfg(test)]
 mod tests {
+    use crate::tokenizer::Delimiter;
+
     use super::*;
 
     #[test]
@@ -72,7 +74,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![]
+                remaining: &[]
             })
         );
     }
@@ -89,7 +91,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![]
+                remaining: &[]
             })
         );
     }
@@ -114,7 +116,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon))]
+                remaining: &[ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon))]
             })
         );
     }
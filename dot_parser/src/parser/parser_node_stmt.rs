@@ -0,0 +1,92 @@
+use super::{
+    grammer::NodeStmt,
+    parser::{ParseBufferItem, ParseResult, Parser},
+    parser_attr_list::AttrList,
+    parser_node_id::NodeId,
+};
+
+// node_stmt : node_id [ attr_list ]
+impl Parser<NodeStmt> for NodeStmt {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, NodeStmt>> {
+        let node_id = NodeId::default().parse(input)?;
+        let attr_list = AttrList::default().parse(node_id.remaining);
+        match attr_list {
+            None => Some(ParseResult {
+                result: NodeStmt {
+                    id: node_id.result,
+                    attributes: None,
+                },
+                remaining: node_id.remaining,
+            }),
+            Some(attr_list) => Some(ParseResult {
+                result: NodeStmt {
+                    id: node_id.result,
+                    attributes: Some(attr_list.result.items),
+                },
+                remaining: attr_list.remaining,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::parser_attribute::Attribute,
+        tokenizer::{Delimiter, Token},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_parse_node_stmt() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon)),
+        ];
+        let expected = NodeStmt {
+            id: NodeId {
+                id: "a".to_string(),
+                port: None,
+            },
+            attributes: None,
+        };
+        let result = NodeStmt::default().parse(&input);
+        assert_eq!(
+            result,
+            Some(ParseResult {
+                result: expected,
+                remaining: &[ParseBufferItem::Token(Token::Delimiter(
+                    Delimiter::Semicolon
+                ))]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_node_stmt_with_attributes() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenSquareBrace)),
+            ParseBufferItem::Token(Token::Identifier("color".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Equal)),
+            ParseBufferItem::Token(Token::Identifier("red".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::ClosedSquareBrace)),
+        ];
+        let expected = NodeStmt {
+            id: NodeId {
+                id: "a".to_string(),
+                port: None,
+            },
+            attributes: Some(vec![Attribute::new("color".to_string(), "red".to_string())]),
+        };
+        let result = NodeStmt::default().parse(&input);
+        assert_eq!(
+            result,
+            Some(ParseResult {
+                result: expected,
+                remaining: &[]
+            })
+        );
+    }
+}
@@ -5,31 +5,21 @@ use super::{
     parser_compass::Compass,
 };
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Port {
     pub id: Option<String>,
     pub compass: Option<Compass>,
 }
 
-impl Default for Port {
-    fn default() -> Self {
-        Port {
-            id: None,
-            compass: None,
-        }
-    }
-}
-
 impl Parser<Port> for Port {
-    fn parse(&self, input: &[ParseBufferItem]) -> Option<super::parser::ParseResult<Port>> {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, Port>> {
         let first = input.first()?;
         let second = input.get(1)?;
         if *first != ParseBufferItem::Token(Token::Delimiter(Delimiter::Colon)) {
             return None;
         }
 
-        let second_as_vec = vec![second.clone()];
-        let second_as_compass = Compass::default().parse(&second_as_vec);
+        let second_as_compass = Compass::default().parse(&input[1..2]);
         let second_as_id = match second {
             ParseBufferItem::Token(Token::Identifier(ref val)) => Some(val),
             _ => None,
@@ -47,7 +37,7 @@ impl Parser<Port> for Port {
                     id: None,
                     compass: Some(second_compass.result),
                 },
-                remaining: input[2..].to_vec(),
+                remaining: &input[2..],
             });
         }
 
@@ -61,8 +51,7 @@ impl Parser<Port> for Port {
                     Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Colon))),
                     Some(ParseBufferItem::Token(Token::Identifier(_))),
                 ) => {
-                    let fourth_as_vec = vec![fourth?.clone()];
-                    let fourth_as_compass = Compass::default().parse(&fourth_as_vec);
+                    let fourth_as_compass = Compass::default().parse(&input[3..4]);
                     if fourth_as_compass.is_some() {
                         let fourth_compass = fourth_as_compass?;
                         return Some(ParseResult {
@@ -70,7 +59,7 @@ impl Parser<Port> for Port {
                                 id: Some(second_as_id.to_string()),
                                 compass: Some(fourth_compass.result),
                             },
-                            remaining: input[4..].to_vec(),
+                            remaining: &input[4..],
                         });
                     }
                 }
@@ -80,7 +69,7 @@ impl Parser<Port> for Port {
                             id: Some(second_as_id.to_string()),
                             compass: None,
                         },
-                        remaining: input[2..].to_vec(),
+                        remaining: &input[2..],
                     });
                 }
             };
@@ -113,7 +102,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![]
+                remaining: &[]
             })
         );
     }
@@ -139,7 +128,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![
+                remaining: &[
                     ParseBufferItem::Token(Token::Identifier("port".to_string())),
                     ParseBufferItem::Token(Token::Delimiter(Delimiter::Colon))
                 ]
@@ -168,7 +157,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![
+                remaining: &[
                     ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenSquareBrace)),
                     ParseBufferItem::Token(Token::Identifier("port".to_string())),
                 ]
@@ -199,7 +188,7 @@ mod tests {
             result,
             Some(ParseResult {
                 result: expected,
-                remaining: vec![
+                remaining: &[
                     ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenSquareBrace)),
                     ParseBufferItem::Token(Token::Identifier("port".to_string()))
                 ]
@@ -0,0 +1,175 @@
+use crate::tokenizer::{Delimiter, Keyword, Token};
+
+use super::{
+    grammer::{AttributeStmt, EdgeOp, EdgeStmt, EdgeStmtSide, NodeStmt, Statement, SubGraph},
+    parser::{ParseBufferItem, ParseResult, Parser},
+    parser_attribute::Attribute,
+    parser_attribute_stmt::AttrStmt,
+    parser_edge_stmt::parse_edge_stmt_rest,
+};
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct StmtList {
+    pub items: Vec<Statement>,
+}
+
+fn starts_subgraph(input: &[ParseBufferItem]) -> bool {
+    matches!(
+        input.first(),
+        Some(ParseBufferItem::Token(
+            Token::Keyword(Keyword::SubGraph) | Token::Delimiter(Delimiter::OpenCurlyBrace)
+        ))
+    )
+}
+
+// stmt : node_stmt | edge_stmt | attr_stmt | ID '=' ID | subgraph
+// Order matters here: edge_stmt starts like node_stmt/subgraph, and ID '=' ID starts like node_stmt
+pub(super) fn parse_stmt(input: &[ParseBufferItem]) -> Option<ParseResult<'_, Statement>> {
+    if let Some(attr_stmt) = AttrStmt::default().parse(input) {
+        return Some(ParseResult {
+            result: Statement::AttrStmt(attr_stmt.result),
+            remaining: attr_stmt.remaining,
+        });
+    }
+    // A subgraph is parsed once and then checked for an edge, trying edge_stmt and subgraph
+    // separately would parse every nested subgraph twice per level
+    if starts_subgraph(input) {
+        let subgraph = SubGraph::default().parse(input)?;
+        if EdgeOp::default().parse(subgraph.remaining).is_none() {
+            return Some(ParseResult {
+                result: Statement::SubGraph(subgraph.result),
+                remaining: subgraph.remaining,
+            });
+        }
+        let edge_lhs = EdgeStmtSide::SubGraph(subgraph.result);
+        let edge_stmt = parse_edge_stmt_rest(edge_lhs, subgraph.remaining)?;
+        return Some(ParseResult {
+            result: Statement::EdgeStmt(edge_stmt.result),
+            remaining: edge_stmt.remaining,
+        });
+    }
+    if let Some(edge_stmt) = EdgeStmt::default().parse(input) {
+        return Some(ParseResult {
+            result: Statement::EdgeStmt(edge_stmt.result),
+            remaining: edge_stmt.remaining,
+        });
+    }
+    if let Some(attribute) = Attribute::default().parse(input) {
+        return Some(ParseResult {
+            result: Statement::AttributeStmt(AttributeStmt {
+                lhs: attribute.result.lhs,
+                rhs: attribute.result.rhs,
+                quoted_lhs: attribute.result.quoted_lhs,
+            }),
+            remaining: attribute.remaining,
+        });
+    }
+    let node_stmt = NodeStmt::default().parse(input)?;
+    Some(ParseResult {
+        result: Statement::NodeStmt(node_stmt.result),
+        remaining: node_stmt.remaining,
+    })
+}
+
+// stmt_list : [ stmt [ ';' ] stmt_list ]
+// An empty list is valid, so this parser always succeeds; callers check what remains
+impl Parser<StmtList> for StmtList {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, StmtList>> {
+        let mut items: Vec<Statement> = vec![];
+        let mut rest = input;
+
+        while let Some(stmt) = parse_stmt(rest) {
+            items.push(stmt.result);
+            rest = stmt.remaining;
+            if let Some(ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon))) =
+                rest.first()
+            {
+                rest = &rest[1..];
+            }
+        }
+
+        Some(ParseResult {
+            result: StmtList { items },
+            remaining: rest,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{
+            grammer::{EdgeOp, EdgeRhs, EdgeStmtSide},
+            parser_node_id::NodeId,
+        },
+        tokenizer::Keyword,
+    };
+
+    use super::*;
+
+    fn node_id(id: &str) -> NodeId {
+        NodeId {
+            id: id.to_string(),
+            port: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_stmt_list() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Identifier("rankdir".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Equal)),
+            ParseBufferItem::Token(Token::Identifier("LR".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon)),
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::DirectedEdge)),
+            ParseBufferItem::Token(Token::Identifier("b".to_string())),
+            ParseBufferItem::Token(Token::Identifier("c".to_string())),
+        ];
+        let expected = StmtList {
+            items: vec![
+                Statement::AttributeStmt(AttributeStmt {
+                    lhs: "rankdir".to_string(),
+                    rhs: "LR".to_string(),
+                    quoted_lhs: false,
+                }),
+                Statement::EdgeStmt(EdgeStmt {
+                    edge_lhs: EdgeStmtSide::NodeId(node_id("a")),
+                    edge_rhs: EdgeRhs {
+                        edge_op: EdgeOp::Directed,
+                        edge_to: EdgeStmtSide::NodeId(node_id("b")),
+                        edge_optional: None,
+                    },
+                    attributes: None,
+                }),
+                Statement::NodeStmt(NodeStmt {
+                    id: node_id("c"),
+                    attributes: None,
+                }),
+            ],
+        };
+        let result = StmtList::default().parse(&input);
+        assert_eq!(
+            result,
+            Some(ParseResult {
+                result: expected,
+                remaining: &[]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_stmt_list_stops_at_unknown_token() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon)),
+            ParseBufferItem::Token(Token::Keyword(Keyword::Strict)),
+        ];
+        let result = StmtList::default().parse(&input).unwrap();
+        assert_eq!(result.result.items.len(), 1);
+        assert_eq!(
+            result.remaining,
+            vec![ParseBufferItem::Token(Token::Keyword(Keyword::Strict))]
+        );
+    }
+}
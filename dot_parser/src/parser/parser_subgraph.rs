@@ -0,0 +1,120 @@
+use crate::tokenizer::{Delimiter, Keyword, Token};
+
+use super::{
+    grammer::SubGraph,
+    parser::{ParseBufferItem, ParseResult, Parser},
+    parser_stmt_list::StmtList,
+};
+
+// subgraph : [ subgraph [ ID ] ] '{' stmt_list '}'
+impl Parser<SubGraph> for SubGraph {
+    fn parse<'a>(&self, input: &'a [ParseBufferItem]) -> Option<ParseResult<'a, SubGraph>> {
+        let mut rest = input;
+        let mut id = None;
+
+        if let Some(ParseBufferItem::Token(Token::Keyword(Keyword::SubGraph))) = rest.first() {
+            rest = &rest[1..];
+            if let Some(ParseBufferItem::Token(Token::Identifier(val))) = rest.first() {
+                id = Some(val.to_string());
+                rest = &rest[1..];
+            }
+        }
+
+        if rest.first()? != &ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenCurlyBrace)) {
+            return None;
+        }
+
+        let stmt_list = StmtList::default().parse(&rest[1..])?;
+        let rest = stmt_list.remaining;
+
+        if rest.first()? != &ParseBufferItem::Token(Token::Delimiter(Delimiter::ClosedCurlyBrace)) {
+            return None;
+        }
+
+        Some(ParseResult {
+            result: SubGraph {
+                id,
+                statements: stmt_list.result.items,
+            },
+            remaining: &rest[1..],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::grammer::{NodeStmt, Statement};
+    use crate::parser::parser_node_id::NodeId;
+
+    use super::*;
+
+    fn node_stmt(id: &str) -> Statement {
+        Statement::NodeStmt(NodeStmt {
+            id: NodeId {
+                id: id.to_string(),
+                port: None,
+            },
+            attributes: None,
+        })
+    }
+
+    #[test]
+    fn test_parse_subgraph() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Keyword(Keyword::SubGraph)),
+            ParseBufferItem::Token(Token::Identifier("cluster_0".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenCurlyBrace)),
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::Semicolon)),
+            ParseBufferItem::Token(Token::Identifier("b".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::ClosedCurlyBrace)),
+        ];
+        let expected = SubGraph {
+            id: Some("cluster_0".to_string()),
+            statements: vec![node_stmt("a"), node_stmt("b")],
+        };
+        let result = SubGraph::default().parse(&input);
+        assert_eq!(
+            result,
+            Some(ParseResult {
+                result: expected,
+                remaining: &[]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_anonymous_subgraph_with_remaining() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenCurlyBrace)),
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+            ParseBufferItem::Token(Token::Identifier("b".to_string())),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::ClosedCurlyBrace)),
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::DirectedEdge)),
+        ];
+        let expected = SubGraph {
+            id: None,
+            statements: vec![node_stmt("a"), node_stmt("b")],
+        };
+        let result = SubGraph::default().parse(&input);
+        assert_eq!(
+            result,
+            Some(ParseResult {
+                result: expected,
+                remaining: &[ParseBufferItem::Token(Token::Delimiter(
+                    Delimiter::DirectedEdge
+                ))]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_subgraph_fail_unclosed() {
+        let input = vec![
+            ParseBufferItem::Token(Token::Delimiter(Delimiter::OpenCurlyBrace)),
+            ParseBufferItem::Token(Token::Identifier("a".to_string())),
+        ];
+        let result = SubGraph::default().parse(&input);
+        assert_eq!(result, None);
+    }
+}
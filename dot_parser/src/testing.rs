@@ -0,0 +1,64 @@
+use crate::{
+    parser::parse,
+    tokenizer::{tokenize, Delimiter, Keyword, Token},
+};
+
+// Tokens that printing may add or drop without changing the graph:
+// separators are optional and `subgraph` before an anonymous `{` is implied
+fn semantic_tokens(tokens: Vec<Token>) -> Vec<Token> {
+    let mut kept: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Delimiter(Delimiter::Semicolon | Delimiter::Comma) => {}
+            Token::Keyword(Keyword::SubGraph)
+                if tokens.peek() == Some(&Token::Delimiter(Delimiter::OpenCurlyBrace)) => {}
+            token => kept.push(token),
+        }
+    }
+    kept
+}
+
+// Panics unless `code` parses, prints, and the printed graph tokenizes to the same tokens as
+// `code` did, give or take separators. Catches Display writing something parse reads differently
+pub fn assert_roundtrip(code: &str) {
+    let tokens = tokenize(code.to_string()).expect("input does not tokenize");
+    let dg = parse(&tokens).expect("input does not parse");
+    let printed = dg.to_string();
+    let reprinted_tokens = match tokenize(printed.clone()) {
+        Ok(tokens) => tokens,
+        Err(err) => panic!("printed graph does not tokenize: {}\n{}", err, printed),
+    };
+    assert_eq!(
+        semantic_tokens(reprinted_tokens),
+        semantic_tokens(tokens),
+        "printed graph reads back differently:\n{}",
+        printed
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assert_roundtrip_fixtures() {
+        let fixtures = [
+            "digraph G { a -> b -> c; b -> d }",
+            "strict graph { a -- b [weight=2, color=\"light blue\"]; }",
+            "digraph { rankdir=LR; node [shape=box]; edge [style=dashed] a:n -> b:p1:sw }",
+            "graph { subgraph cluster_x { label=\"X\"; a b } {c d} -- e; subgraph { f } }",
+            "digraph { \"with space\" -> \"esc \\\" quote\"; -1.5 -> .5 }",
+            "// comment\ngraph g {\n  /* block */ a -- b\n# 1 \"preprocessed.gv\"\n}",
+        ];
+        for code in fixtures {
+            assert_roundtrip(code);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "does not parse")]
+    fn test_assert_roundtrip_rejects_bad_input() {
+        assert_roundtrip("digraph { a -> }");
+    }
+}
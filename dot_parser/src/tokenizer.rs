@@ -1,5 +1,8 @@
 use regex::Regex;
 use std::char;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read};
+use std::sync::LazyLock;
 
 use anyhow::{bail, Ok, Result};
 
@@ -14,6 +17,20 @@ pub enum Keyword {
     Strict,
 }
 
+impl Keyword {
+    // Lowercase spelling, keywords are case insensitive
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Keyword::Node => "node",
+            Keyword::Edge => "edge",
+            Keyword::Graph => "graph",
+            Keyword::Digraph => "digraph",
+            Keyword::SubGraph => "subgraph",
+            Keyword::Strict => "strict",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Delimiter {
     Colon,             // :
@@ -30,22 +47,86 @@ pub enum Delimiter {
     DoubleQuote,       // "
 }
 
+impl Delimiter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Delimiter::Colon => ":",
+            Delimiter::Comma => ",",
+            Delimiter::Semicolon => ";",
+            Delimiter::OpenCurlyBrace => "{",
+            Delimiter::ClosedCurlyBrace => "}",
+            Delimiter::OpenSquareBrace => "[",
+            Delimiter::ClosedSquareBrace => "]",
+            Delimiter::Space => " ",
+            Delimiter::Equal => "=",
+            Delimiter::UndirectedEdge => "--",
+            Delimiter::DirectedEdge => "->",
+            Delimiter::DoubleQuote => "\"",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     // A string of alphabetic ([a-zA-Z\200-\377]) characters, underscores ('_') or digits([0-9]), not beginning with a digit;
     // A numeral [-]?(.[0-9]⁺ | [0-9]⁺(.[0-9]*)? );
     // any double-quoted string ("...") possibly containing escaped quotes (\")¹;
     Identifier(String),
+    // A quoted identifier right before '=', so an attribute key remembers it was quoted
+    QuotedKey(String),
     Keyword(Keyword),
     Delimiter(Delimiter),
 }
 
+impl Token {
+    pub fn is_delimiter(&self) -> bool {
+        matches!(self, Token::Delimiter(_))
+    }
+
+    pub fn is_keyword(&self) -> bool {
+        matches!(self, Token::Keyword(_))
+    }
+
+    pub fn is_identifier(&self) -> bool {
+        matches!(self, Token::Identifier(_) | Token::QuotedKey(_))
+    }
+
+    pub fn as_identifier(&self) -> Option<&str> {
+        match self {
+            Token::Identifier(id) | Token::QuotedKey(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    // The token as written, minus the quotes of a quoted identifier
+    pub fn as_str(&self) -> &str {
+        match self {
+            Token::Identifier(id) | Token::QuotedKey(id) => id,
+            Token::Keyword(keyword) => keyword.as_str(),
+            Token::Delimiter(delimiter) => delimiter.as_str(),
+        }
+    }
+}
+
+// Escapes graphviz understands inside strings: \" \\ line continuation and the label escapes
+const KNOWN_ESCAPES: [char; 12] = ['"', '\\', '\n', 'n', 'l', 'r', 'N', 'G', 'E', 'T', 'H', 'L'];
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenizeOptions {
+    // Reject escapes outside KNOWN_ESCAPES instead of keeping them as is
+    pub strict_escapes: bool,
+    // Accept unquoted ids made of any unicode letters (e.g. 日本語), not only Latin-1
+    pub unicode_identifiers: bool,
+    // Accept numerals with a leading + (e.g. +3, +.5), kept as written
+    pub plus_numerals: bool,
+}
+
 #[derive(Debug)]
-struct TokenizeError {
-    line: usize,
-    col: usize,
-    token: String,
-    reason: Option<String>,
+pub struct TokenizeError {
+    pub line: usize,
+    pub col: usize,
+    pub token: String,
+    pub reason: Option<String>,
 }
 
 impl std::fmt::Display for TokenizeError {
@@ -58,6 +139,14 @@ impl std::fmt::Display for TokenizeError {
     }
 }
 
+// Compiled once, every identifier in the input is checked against them
+static ALPHABETIC_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z\x80-\xFF_][a-zA-Z\x80-\xFF_0-9]*$").unwrap());
+static NUMERAL_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^-?(?:\.[0-9]+|[0-9]+(?:\.[0-9]*)?)$").unwrap());
+static QUOTED_STRING_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^"([^"\\]|\\.)*"$"#).unwrap());
+
 fn is_proper_identifier(s: &str, line: usize, col: usize) -> Result<()> {
     if s.len() == 1 {
         let s: char = s.chars().next().unwrap();
@@ -83,12 +172,8 @@ fn is_proper_identifier(s: &str, line: usize, col: usize) -> Result<()> {
             reason: Some("Empty quotes".to_string()),
         });
     }
-    let alphabetic_id = Regex::new(r"^[a-zA-Z\x80-\xFF_][a-zA-Z\x80-\xFF_0-9]*$").unwrap();
-    let numeral_id = Regex::new(r"^-?(?:\.[0-9]+|[0-9]+(?:\.[0-9]*)?)$").unwrap();
-    let quoted_string_id = Regex::new(r#"^"([^"\\]|\\.)*"$"#).unwrap();
-
     let result =
-        alphabetic_id.is_match(s) || numeral_id.is_match(s) || quoted_string_id.is_match(s);
+        ALPHABETIC_ID.is_match(s) || NUMERAL_ID.is_match(s) || QUOTED_STRING_ID.is_match(s);
     if !result {
         bail!(TokenizeError {
             line,
@@ -100,9 +185,33 @@ fn is_proper_identifier(s: &str, line: usize, col: usize) -> Result<()> {
     Ok(())
 }
 
+// Unicode letter or '_' followed by unicode letters, digits or '_'
+fn is_unicode_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) if first.is_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+// An unsigned numeral after a single leading +
+static PLUS_NUMERAL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\+(?:\.[0-9]+|[0-9]+(?:\.[0-9]*)?)$").unwrap());
+
+fn is_plus_numeral(s: &str) -> bool {
+    PLUS_NUMERAL.is_match(s)
+}
+
 // note - this flowcan be made more idiomatic ~
 // split into -> fn identify_keyword() & fn convert_to_idntifier()
-fn chars_to_token(chars: Vec<char>, line: usize, col: usize) -> Result<Option<Token>> {
+fn chars_to_token(
+    chars: Vec<char>,
+    line: usize,
+    col: usize,
+    options: &TokenizeOptions,
+) -> Result<Option<Token>> {
     if chars.is_empty() {
         return Ok(None);
     }
@@ -117,7 +226,11 @@ fn chars_to_token(chars: Vec<char>, line: usize, col: usize) -> Result<Option<To
         "strict" => Token::Keyword(Keyword::Strict),
         _ => {
             let mut word: String = chars.iter().collect();
-            is_proper_identifier(&word, line, col)?;
+            let allowed_by_options = (options.unicode_identifiers && is_unicode_identifier(&word))
+                || (options.plus_numerals && is_plus_numeral(&word));
+            if !allowed_by_options {
+                is_proper_identifier(&word, line, col)?;
+            }
             // remove first and last quote
             if word.starts_with('"') && word.ends_with('"') {
                 word.pop();
@@ -129,95 +242,280 @@ fn chars_to_token(chars: Vec<char>, line: usize, col: usize) -> Result<Option<To
     Ok(Some(tkn))
 }
 
-pub fn tokenize(code: String) -> Result<Vec<Token>> {
-    let mut parse_line: usize = 0;
-    let mut col: usize = 0;
-    let mut token_buffer: Vec<char> = Vec::new();
-    let mut tokens: Vec<Token> = Vec::new();
-    let mut handling_double_quote = false;
-    let mut espace_next_char = false;
-
-    let mut possible_edge = false;
-    for current_char in code.chars() {
-        /*
-        println!(
-            "current_char: {}, line: {}, col: {}",
-            current_char, parse_line, col
-        );
-        println!("tokens: {:?}", tokens);
-        println!("prev_buffer: {:?} \n\n\n", token_buffer);
-        */
-        col += 1;
+// Byte offsets into the source (end is exclusive), plus line (from 0) and col (from 1) of the start
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+struct Lexer {
+    options: TokenizeOptions,
+    // keep going after an error, collecting every error instead of stopping at the first
+    recover: bool,
+    line: usize,
+    col: usize,
+    token_buffer: Vec<char>,
+    buffer_span: Span,
+    tokens: Vec<Spanned<Token>>,
+    errors: Vec<TokenizeError>,
+    handling_double_quote: bool,
+    espace_next_char: bool,
+    after_newline: bool,
+    in_line_comment: bool,
+    in_block_comment: bool,
+    possible_edge: bool,
+    // the last token pushed was '=', a keyword now is an attribute value and keeps its spelling
+    after_equal: bool,
+    // the last token pushed was a quoted identifier, a '=' now makes it a QuotedKey
+    after_quoted: bool,
+    // the lookahead char was already consumed by the previous char ('*' of "/*", '/' of "*/")
+    skip_next: bool,
+}
 
-        if possible_edge {
+impl Lexer {
+    fn new(options: &TokenizeOptions, recover: bool) -> Lexer {
+        Lexer {
+            options: options.clone(),
+            recover,
+            line: 0,
+            col: 0,
+            token_buffer: Vec::new(),
+            buffer_span: Span::default(),
+            tokens: Vec::new(),
+            errors: Vec::new(),
+            handling_double_quote: false,
+            espace_next_char: false,
+            after_newline: false,
+            in_line_comment: false,
+            in_block_comment: false,
+            possible_edge: false,
+            after_equal: false,
+            after_quoted: false,
+            skip_next: false,
+        }
+    }
+
+    // returns true when lexing should stop
+    fn error(&mut self, err: anyhow::Error) -> bool {
+        match err.downcast::<TokenizeError>() {
+            Result::Ok(err) => self.errors.push(err),
+            Err(err) => self.errors.push(TokenizeError {
+                line: self.line,
+                col: self.col,
+                token: String::new(),
+                reason: Some(err.to_string()),
+            }),
+        }
+        !self.recover
+    }
+
+    fn push_char(&mut self, c: char, offset: usize) {
+        if self.token_buffer.is_empty() {
+            self.buffer_span = Span {
+                start: offset,
+                end: offset,
+                line: self.line,
+                col: self.col,
+            };
+        }
+        self.token_buffer.push(c);
+    }
+
+    fn push_token(&mut self, token: Token, start: usize, end: usize) {
+        self.after_equal = token == Token::Delimiter(Delimiter::Equal);
+        if self.after_equal && self.after_quoted {
+            if let Some(key) = self.tokens.last_mut() {
+                if let Token::Identifier(id) = &mut key.value {
+                    key.value = Token::QuotedKey(std::mem::take(id));
+                }
+            }
+        }
+        self.after_quoted = false;
+        self.tokens.push(Spanned {
+            value: token,
+            span: Span {
+                start,
+                end,
+                line: self.line,
+                col: self.col,
+            },
+        });
+    }
+
+    // converts whatever is in the buffer into a token ending at `end`; returns true when lexing should stop
+    fn flush(&mut self, end: usize) -> bool {
+        let chars = std::mem::take(&mut self.token_buffer);
+        let word: String = chars.iter().collect();
+        let quoted = word.starts_with('"');
+        match chars_to_token(chars, self.line, self.col, &self.options) {
+            Result::Ok(Some(token)) => {
+                // `label=Node` is the string "Node", not the keyword
+                let token = match token {
+                    Token::Keyword(_) if self.after_equal => Token::Identifier(word),
+                    token => token,
+                };
+                self.after_equal = false;
+                self.after_quoted = quoted && token.is_identifier();
+                let mut span = self.buffer_span.clone();
+                span.end = end;
+                self.tokens.push(Spanned { value: token, span });
+                false
+            }
+            Result::Ok(None) => false,
+            Err(err) => self.error(err),
+        }
+    }
+
+    // Lexes one char, `next_char` is the one after it (None at the end of the input)
+    // Returns true when lexing should stop
+    fn step(&mut self, offset: usize, current_char: char, next_char: Option<char>) -> bool {
+        if self.skip_next {
+            self.skip_next = false;
+            return false;
+        }
+        if self.after_newline {
+            self.line += 1;
+            self.col = 0;
+        }
+        self.after_newline = current_char == '\n';
+        self.col += 1;
+
+        // comments are dropped like spaces
+        if self.in_line_comment {
+            self.in_line_comment = current_char != '\n';
+            return false;
+        }
+        if self.in_block_comment {
+            if current_char == '*' && next_char == Some('/') {
+                self.skip_next = true;
+                self.col += 1;
+                self.in_block_comment = false;
+            }
+            return false;
+        }
+
+        if self.possible_edge {
+            self.possible_edge = false;
             // remove last item, it is a optimistic Delimiter::UndirectedEdge
-            tokens.pop();
+            let Some(edge) = self.tokens.pop() else {
+                return false;
+            };
             if current_char == '-' {
-                tokens.push(Token::Delimiter(Delimiter::UndirectedEdge));
-                possible_edge = false;
-                continue;
+                self.tokens.push(Spanned {
+                    value: Token::Delimiter(Delimiter::UndirectedEdge),
+                    span: Span {
+                        end: offset + 1,
+                        ..edge.span
+                    },
+                });
+                return false;
             }
             if current_char == '>' {
-                tokens.push(Token::Delimiter(Delimiter::DirectedEdge));
-                possible_edge = false;
-                continue;
+                self.tokens.push(Spanned {
+                    value: Token::Delimiter(Delimiter::DirectedEdge),
+                    span: Span {
+                        end: offset + 1,
+                        ..edge.span
+                    },
+                });
+                return false;
             }
-            bail!(TokenizeError {
-                line: parse_line,
-                col,
+            // recovering: the bad char is dropped along with the edge
+            return self.error(anyhow::anyhow!(TokenizeError {
+                line: self.line,
+                col: self.col,
                 token: current_char.to_string(),
                 reason: Some("Invalid edge, expected - or >".to_string()),
-            })
+            }));
         }
 
         // escape must be processed first
-        if current_char == '\\' {
-            espace_next_char = true;
-            token_buffer.push(current_char);
-            continue;
+        if current_char == '\\' && !self.espace_next_char {
+            self.espace_next_char = true;
+            self.push_char(current_char, offset);
+            return false;
         }
-        if espace_next_char {
-            espace_next_char = false;
-            token_buffer.push(current_char);
-            continue;
+        if self.espace_next_char {
+            self.espace_next_char = false;
+            if self.options.strict_escapes && !KNOWN_ESCAPES.contains(&current_char) {
+                let stop = self.error(anyhow::anyhow!(TokenizeError {
+                    line: self.line,
+                    col: self.col,
+                    token: format!("\\{}", current_char),
+                    reason: Some("Unknown escape sequence".to_string()),
+                }));
+                if stop {
+                    return true;
+                }
+            }
+            self.push_char(current_char, offset);
+            return false;
         }
 
         // double-quote handling
-        if handling_double_quote && current_char != '\"' {
-            token_buffer.push(current_char);
-            continue;
-        }
-        if current_char == '\"' && handling_double_quote {
-            handling_double_quote = false;
-            token_buffer.push(current_char);
-            let current_identifier = chars_to_token(token_buffer, parse_line, col)?;
-            if let Some(identifier) = current_identifier {
-                tokens.push(identifier);
+        if self.handling_double_quote && current_char != '\"' {
+            self.push_char(current_char, offset);
+            return false;
+        }
+        if current_char == '\"' && self.handling_double_quote {
+            self.handling_double_quote = false;
+            self.push_char(current_char, offset);
+            return self.flush(offset + 1);
+        }
+        if current_char == '\"' && !self.handling_double_quote {
+            self.handling_double_quote = true;
+            // a"b" is not two ids, there has to be a delimiter before the quote
+            if !self.token_buffer.is_empty() {
+                let stop = self.error(anyhow::anyhow!(TokenizeError {
+                    line: self.buffer_span.line,
+                    col: self.buffer_span.col,
+                    token: self.token_buffer.iter().chain(['"'].iter()).collect(),
+                    reason: Some("Expected a delimiter before the quoted string".to_string()),
+                }));
+                if stop {
+                    return true;
+                }
             }
-            token_buffer = vec![];
-            continue;
-        }
-        if current_char == '\"' && !handling_double_quote {
-            handling_double_quote = true;
-            let prev_tkn = chars_to_token(token_buffer, parse_line, col)?;
-            if let Some(identifier) = prev_tkn {
-                tokens.push(identifier);
+            if self.flush(offset) {
+                return true;
             }
-
-            token_buffer = vec![current_char];
-            continue;
+            self.push_char(current_char, offset);
+            return false;
         }
         // end double-quote handling
 
+        // start of a comment: // and /* */, or a # line (C preprocessor output)
+        let starts_line_comment = (current_char == '/' && next_char == Some('/'))
+            || (current_char == '#' && self.col == 1);
+        let starts_block_comment = current_char == '/' && next_char == Some('*');
+        if starts_line_comment || starts_block_comment {
+            if self.flush(offset) {
+                return true;
+            }
+            if starts_block_comment {
+                // consume the '*' so that "/*/" does not close the comment
+                self.skip_next = true;
+                self.col += 1;
+            }
+            self.in_line_comment = starts_line_comment;
+            self.in_block_comment = starts_block_comment;
+            return false;
+        }
+
         // other delimiters
         let delim = match current_char {
             // start of quote
             // newline and space are same
-            '\n' => {
-                parse_line += 1;
-                col = 0;
-                Some(Token::Delimiter(Delimiter::Space))
-            }
+            '\n' => Some(Token::Delimiter(Delimiter::Space)),
             ' ' => Some(Token::Delimiter(Delimiter::Space)),
             ':' => Some(Token::Delimiter(Delimiter::Colon)),
             ',' => Some(Token::Delimiter(Delimiter::Comma)),
@@ -227,8 +525,14 @@ pub fn tokenize(code: String) -> Result<Vec<Token>> {
             '{' => Some(Token::Delimiter(Delimiter::OpenCurlyBrace)),
             '}' => Some(Token::Delimiter(Delimiter::ClosedCurlyBrace)),
             '=' => Some(Token::Delimiter(Delimiter::Equal)),
+            // a lone '-' before a digit or '.' starts a negative numeral like -1.5 or -.5
+            '-' if self.token_buffer.is_empty()
+                && next_char.is_some_and(|c| c.is_ascii_digit() || c == '.') =>
+            {
+                None
+            }
             '-' => {
-                possible_edge = true;
+                self.possible_edge = true;
                 // this will be over_written in the delimiter if/else
                 Some(Token::Delimiter(Delimiter::UndirectedEdge))
             }
@@ -236,38 +540,292 @@ pub fn tokenize(code: String) -> Result<Vec<Token>> {
         };
         match delim {
             Some(delimiter) => {
-                let prev_tkn = chars_to_token(token_buffer, parse_line, col)?;
-                if let Some(identifier) = prev_tkn {
-                    tokens.push(identifier);
-                }
-                // reset token_buffer
-                token_buffer = vec![];
-                // combine multiple spaces(and newline) to one
-                /*let mut skip_space = false;
-                if let Some(last_token) = tokens.last() {
-                    skip_space = delimiter == Token::Delimiter(Delimiter::Space)
-                        && *last_token == Token::Delimiter(Delimiter::Space);
+                if self.flush(offset) {
+                    return true;
                 }
-                */
                 // In dot language, spaces are not syntatically meaningful
                 // They are only useful inside quoted strings
                 // So, we skip spaces
                 if delimiter != Token::Delimiter(Delimiter::Space) {
-                    tokens.push(delimiter);
+                    self.push_token(delimiter, offset, offset + 1);
                 }
             }
             _ => {
-                token_buffer.push(current_char);
+                self.push_char(current_char, offset);
             }
         };
+        false
+    }
+
+    // `end` is the length of the whole input
+    fn finish(&mut self, end: usize) {
+        // the last token may run up to the end of the input
+        self.flush(end);
+        if self.possible_edge {
+            self.possible_edge = false;
+            self.tokens.pop();
+            self.error(anyhow::anyhow!(TokenizeError {
+                line: self.line,
+                col: self.col,
+                token: "-".to_string(),
+                reason: Some("Invalid edge, expected - or >".to_string()),
+            }));
+        }
+    }
+}
+
+fn lex_with_options(
+    code: &str,
+    options: &TokenizeOptions,
+    recover: bool,
+) -> (Vec<Spanned<Token>>, Vec<TokenizeError>) {
+    let mut lexer = Lexer::new(options, recover);
+    let mut chars = code.char_indices().peekable();
+    while let Some((offset, current_char)) = chars.next() {
+        let next_char = chars.peek().map(|(_, c)| *c);
+        if lexer.step(offset, current_char, next_char) {
+            break;
+        }
+    }
+    lexer.finish(code.len());
+    (lexer.tokens, lexer.errors)
+}
+
+pub fn tokenize(code: String) -> Result<Vec<Token>> {
+    tokenize_with_options(code, &TokenizeOptions::default())
+}
+
+pub fn tokenize_with_options(code: String, options: &TokenizeOptions) -> Result<Vec<Token>> {
+    let (tokens, mut errors) = lex_with_options(&code, options, false);
+    if !errors.is_empty() {
+        bail!(errors.remove(0));
+    }
+    Ok(tokens.into_iter().map(|token| token.value).collect())
+}
+
+// Best effort tokenizing for editors: skips past bad tokens and reports every error found
+pub fn lex(code: &str) -> (Vec<Spanned<Token>>, Vec<TokenizeError>) {
+    lex_with_options(code, &TokenizeOptions::default(), true)
+}
+
+// Lexes code[start_offset..], for editors re-lexing only the part of a document after an edit
+// Spans and error positions are positions in the whole of `code`
+pub fn tokenize_range(code: &str, start_offset: usize) -> Result<Vec<Spanned<Token>>> {
+    let Some(fragment) = code.get(start_offset..) else {
+        bail!(
+            "Offset {} is not a character boundary in the input",
+            start_offset
+        );
+    };
+    let prefix = &code[..start_offset];
+    let base_line = prefix.matches('\n').count();
+    // chars already on the line the fragment starts on
+    let base_col = prefix
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count();
+    let shift = |line: usize, col: usize| match line {
+        0 => (base_line, base_col + col),
+        _ => (base_line + line, col),
+    };
+
+    let (mut tokens, mut errors) = lex_with_options(fragment, &TokenizeOptions::default(), false);
+    if !errors.is_empty() {
+        let mut err = errors.remove(0);
+        (err.line, err.col) = shift(err.line, err.col);
+        bail!(err);
+    }
+    for token in &mut tokens {
+        token.span.start += start_offset;
+        token.span.end += start_offset;
+        (token.span.line, token.span.col) = shift(token.span.line, token.span.col);
     }
     Ok(tokens)
 }
 
+// One token per line with its index, for debugging the lexer:
+// `3  identifier  "a b"`, identifiers are quoted so surrounding spaces and escapes show
+pub fn format_tokens(tokens: &[Token]) -> String {
+    let width = tokens.len().saturating_sub(1).to_string().len();
+    let mut out = String::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        let (kind, text) = match token {
+            Token::Identifier(id) => ("identifier", format!("{:?}", id)),
+            Token::QuotedKey(id) => ("key", format!("{:?}", id)),
+            Token::Keyword(keyword) => ("keyword", keyword.as_str().to_string()),
+            Token::Delimiter(delimiter) => ("delimiter", delimiter.as_str().to_string()),
+        };
+        out.push_str(&format!("{:>width$}  {:<10}  {}\n", idx, kind, text));
+    }
+    out
+}
+
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+// Tokens of a source read piece by piece, see tokenize_reader
+struct ReaderTokens<R> {
+    reader: R,
+    chunk_size: usize,
+    lexer: Lexer,
+    // the start of a char cut in two by the end of a chunk
+    partial_char: Vec<u8>,
+    // the lexer needs one char of lookahead, so the last char read waits for the next one
+    pending_char: Option<(usize, char)>,
+    // byte offset of the next char read
+    offset: usize,
+    ready: VecDeque<Result<Spanned<Token>>>,
+    done: bool,
+}
+
+impl<R: Read> ReaderTokens<R> {
+    fn fail(&mut self, err: anyhow::Error) {
+        self.ready.push_back(Err(err));
+        self.done = true;
+    }
+
+    // moves the finished tokens to `ready`, or the first error once there is one
+    fn drain_lexer(&mut self) {
+        // a trailing '-' may still become part of a "--" or "->", a trailing quoted identifier
+        // may still turn out to be a key
+        let keep = usize::from(self.lexer.possible_edge || self.lexer.after_quoted);
+        let finished = self.lexer.tokens.len().saturating_sub(keep);
+        self.ready
+            .extend(self.lexer.tokens.drain(..finished).map(Ok));
+        if !self.lexer.errors.is_empty() {
+            let err = self.lexer.errors.remove(0);
+            self.fail(anyhow::anyhow!(err));
+        }
+    }
+
+    fn lex_chars(&mut self, text: &str) {
+        for current_char in text.chars() {
+            let offset = self.offset;
+            self.offset += current_char.len_utf8();
+            if let Some((pending_offset, pending)) =
+                self.pending_char.replace((offset, current_char))
+            {
+                if self.lexer.step(pending_offset, pending, Some(current_char)) {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn read_chunk(&mut self) {
+        let mut chunk = vec![0; self.chunk_size];
+        let read = match self.reader.read(&mut chunk) {
+            Result::Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => return,
+            Err(err) => return self.fail(err.into()),
+        };
+        if read == 0 {
+            if !self.partial_char.is_empty() {
+                return self.fail(self.invalid_utf8());
+            }
+            if let Some((offset, pending)) = self.pending_char.take() {
+                self.lexer.step(offset, pending, None);
+            }
+            if self.lexer.errors.is_empty() {
+                self.lexer.finish(self.offset);
+            }
+            self.drain_lexer();
+            self.done = true;
+            return;
+        }
+
+        let mut bytes = std::mem::take(&mut self.partial_char);
+        bytes.extend_from_slice(&chunk[..read]);
+        let (text, rest) = match std::str::from_utf8(&bytes) {
+            Result::Ok(text) => (text, &[][..]),
+            Err(err) => {
+                let (valid, rest) = bytes.split_at(err.valid_up_to());
+                let text = std::str::from_utf8(valid).unwrap_or_default();
+                // a char cut by the chunk end is finished by the next read, anything else is bad input
+                if err.error_len().is_some() {
+                    self.lex_chars(text);
+                    self.drain_lexer();
+                    if !self.done {
+                        self.fail(self.invalid_utf8());
+                    }
+                    return;
+                }
+                (text, rest)
+            }
+        };
+        self.lex_chars(text);
+        self.partial_char = rest.to_vec();
+        self.drain_lexer();
+    }
+
+    fn invalid_utf8(&self) -> anyhow::Error {
+        anyhow::anyhow!(TokenizeError {
+            line: self.lexer.line,
+            col: self.lexer.col,
+            token: String::new(),
+            reason: Some(format!("Invalid UTF-8 at byte {}", self.offset)),
+        })
+    }
+}
+
+impl<R: Read> Iterator for ReaderTokens<R> {
+    type Item = Result<Spanned<Token>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.ready.pop_front() {
+                return Some(item);
+            }
+            if self.done {
+                return None;
+            }
+            self.read_chunk();
+        }
+    }
+}
+
+fn tokenize_reader_with_chunk_size<R: Read>(
+    reader: R,
+    chunk_size: usize,
+) -> impl Iterator<Item = Result<Spanned<Token>>> {
+    ReaderTokens {
+        reader,
+        chunk_size,
+        lexer: Lexer::new(&TokenizeOptions::default(), false),
+        partial_char: Vec::new(),
+        pending_char: None,
+        offset: 0,
+        ready: VecDeque::new(),
+        done: false,
+    }
+}
+
+// Tokenizes a source as it is read, for files too big to hold in one String
+// Yields the same tokens and spans as tokenize_range(code, 0), the first error ends the iteration
+pub fn tokenize_reader<R: Read>(reader: R) -> impl Iterator<Item = Result<Spanned<Token>>> {
+    tokenize_reader_with_chunk_size(reader, READ_CHUNK_SIZE)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_token_classification() {
+        let id = Token::Identifier("a".to_string());
+        let keyword = Token::Keyword(Keyword::Digraph);
+        let delimiter = Token::Delimiter(Delimiter::DirectedEdge);
+
+        assert!(id.is_identifier() && !id.is_keyword() && !id.is_delimiter());
+        assert!(keyword.is_keyword() && !keyword.is_identifier() && !keyword.is_delimiter());
+        assert!(delimiter.is_delimiter() && !delimiter.is_identifier() && !delimiter.is_keyword());
+
+        assert_eq!(id.as_identifier(), Some("a"));
+        assert_eq!(keyword.as_identifier(), None);
+        assert_eq!(delimiter.as_identifier(), None);
+    }
+
     #[test]
     fn test_is_proper_identifier_alphabetic_ids() {
         // Valid alphabetic IDs
@@ -331,7 +889,8 @@ mod tests {
             ("SUBGRAPH", Keyword::SubGraph),
         ];
         for (keyword, expected_token) in keywords.iter() {
-            let keyword_tkn = chars_to_token(keyword.chars().collect(), 0, 0);
+            let keyword_tkn =
+                chars_to_token(keyword.chars().collect(), 0, 0, &TokenizeOptions::default());
             match keyword_tkn {
                 Result::Ok(Some(tkn)) => {
                     assert_eq!(
@@ -356,7 +915,12 @@ mod tests {
     fn test_chars_to_token_identifiers() {
         let keywords = ["my_id", "_abc", "value_1", "value2", "Ü123df"];
         for identifier in keywords.iter() {
-            let keyword_tkn = chars_to_token(identifier.chars().collect(), 0, 0);
+            let keyword_tkn = chars_to_token(
+                identifier.chars().collect(),
+                0,
+                0,
+                &TokenizeOptions::default(),
+            );
             let expected = Token::Identifier(identifier.to_string());
             match keyword_tkn {
                 Result::Ok(Some(tkn)) => {
@@ -380,17 +944,27 @@ mod tests {
     #[test]
     fn test_chars_to_token_quoted_identifiers() {
         assert_eq!(
-            chars_to_token("\"quo ted\"".chars().collect(), 0, 0)
-                .unwrap()
-                .unwrap(),
+            chars_to_token(
+                "\"quo ted\"".chars().collect(),
+                0,
+                0,
+                &TokenizeOptions::default()
+            )
+            .unwrap()
+            .unwrap(),
             Token::Identifier("quo ted".to_string())
         );
         // todo: check this case -> I suspect this is just display issue
         // should be okay when I render
         assert_eq!(
-            chars_to_token(vec!['"', 'q', '\\', '"', 'u', '"'], 0, 0)
-                .unwrap()
-                .unwrap(),
+            chars_to_token(
+                vec!['"', 'q', '\\', '"', 'u', '"'],
+                0,
+                0,
+                &TokenizeOptions::default()
+            )
+            .unwrap()
+            .unwrap(),
             Token::Identifier("q\\\"u".to_string())
         );
     }
@@ -399,11 +973,14 @@ mod tests {
     fn test_chars_to_token_invalid_identifiers() {
         // Invalid identifiers should return an error
         assert_eq!(
-            chars_to_token(vec!['1', '2', '3'], 0, 0).unwrap().unwrap(),
+            chars_to_token(vec!['1', '2', '3'], 0, 0, &TokenizeOptions::default())
+                .unwrap()
+                .unwrap(),
             Token::Identifier("123".to_string())
         ); // Starts with a digit
-        assert!(chars_to_token(vec!['!', 'i', 'd'], 0, 0).is_err()); // Contains invalid character
-        assert!(chars_to_token(vec!['a', '!', 'b'], 0, 0).is_err()); // Contains invalid character
+        assert!(chars_to_token(vec!['!', 'i', 'd'], 0, 0, &TokenizeOptions::default()).is_err()); // Contains invalid character
+        assert!(chars_to_token(vec!['a', '!', 'b'], 0, 0, &TokenizeOptions::default()).is_err());
+        // Contains invalid character
     }
 
     #[test]
@@ -556,4 +1133,318 @@ mod tests {
         ];
         assert_eq!(tokens, expected);
     }
+
+    #[test]
+    fn test_tokenize_unknown_escape_lenient() {
+        let code = "graph { a [label=\"x\\qy\"] }".to_string();
+        let tokens = tokenize(code).unwrap();
+        assert!(tokens.contains(&Token::Identifier("x\\qy".to_string())));
+    }
+
+    #[test]
+    fn test_tokenize_unknown_escape_strict() {
+        let options = TokenizeOptions {
+            strict_escapes: true,
+            ..Default::default()
+        };
+        let code = "graph {\n a [label=\"x\\qy\"] }".to_string();
+        let err = tokenize_with_options(code, &options).unwrap_err();
+        let err = err.downcast_ref::<TokenizeError>().unwrap();
+        assert_eq!((err.line, err.col), (1, 14));
+        assert_eq!(err.token, "\\q");
+
+        let code = "graph { a [label=\"x\\ny\\\"z\"] }".to_string();
+        assert!(tokenize_with_options(code, &options).is_ok());
+    }
+
+    #[test]
+    fn test_tokenize_unicode_identifiers() {
+        let code = "digraph { 日本語 -> café }".to_string();
+        assert!(tokenize(code.clone()).is_err());
+
+        let options = TokenizeOptions {
+            unicode_identifiers: true,
+            ..Default::default()
+        };
+        let tokens = tokenize_with_options(code, &options).unwrap();
+        assert_eq!(tokens[2], Token::Identifier("日本語".to_string()));
+        assert_eq!(tokens[4], Token::Identifier("café".to_string()));
+
+        let code = "digraph { 1日 }".to_string();
+        assert!(tokenize_with_options(code, &options).is_err());
+    }
+
+    #[test]
+    fn test_tokenize_emoji_in_quotes() {
+        let code = "graph { a [label=\"🦀 rust\"] }".to_string();
+        let tokens = tokenize(code).unwrap();
+        assert!(tokens.contains(&Token::Identifier("🦀 rust".to_string())));
+    }
+
+    #[test]
+    fn test_lex_spans() {
+        let (tokens, errors) = lex("graph {\n  a -> \"b c\"\n}");
+        assert!(errors.is_empty());
+        let spans: Vec<(usize, usize, usize, usize)> = tokens
+            .iter()
+            .map(|token| {
+                (
+                    token.span.start,
+                    token.span.end,
+                    token.span.line,
+                    token.span.col,
+                )
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 5, 0, 1),
+                (6, 7, 0, 7),
+                (10, 11, 1, 3),
+                (12, 14, 1, 5),
+                (15, 20, 1, 8),
+                (21, 22, 2, 1),
+            ]
+        );
+        assert_eq!(tokens[4].value, Token::Identifier("b c".to_string()));
+    }
+
+    #[test]
+    fn test_lex_reports_every_error() {
+        let code = "graph { a!b -> c; d -< e; f }";
+        assert!(tokenize(code.to_string()).is_err());
+
+        let (tokens, errors) = lex(code);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].token, "a!b");
+        assert_eq!(errors[1].token, "<");
+        let values: Vec<Token> = tokens.into_iter().map(|token| token.value).collect();
+        assert!(values.contains(&Token::Identifier("c".to_string())));
+        assert!(values.contains(&Token::Identifier("f".to_string())));
+    }
+
+    #[test]
+    fn test_tokenize_skips_comments() {
+        let code = "# 1 \"file.gv\"
+graph G { // the graph
+    a -- /* inline */ b; /* multi
+    line */ c
+    d // trailing -- e
+}"
+        .to_string();
+        let tokens = tokenize(code).unwrap();
+        let expected = vec![
+            Token::Keyword(Keyword::Graph),
+            Token::Identifier("G".to_string()),
+            Token::Delimiter(Delimiter::OpenCurlyBrace),
+            Token::Identifier("a".to_string()),
+            Token::Delimiter(Delimiter::UndirectedEdge),
+            Token::Identifier("b".to_string()),
+            Token::Delimiter(Delimiter::Semicolon),
+            Token::Identifier("c".to_string()),
+            Token::Identifier("d".to_string()),
+            Token::Delimiter(Delimiter::ClosedCurlyBrace),
+        ];
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_tokenize_comment_markers_in_quotes_are_kept() {
+        let code = "graph { a [label=\"// not /* a */ comment\"] }".to_string();
+        let tokens = tokenize(code).unwrap();
+        assert!(tokens.contains(&Token::Identifier("// not /* a */ comment".to_string())));
+    }
+
+    #[test]
+    fn test_tokenize_delimiters_in_quotes_are_kept() {
+        let code = "graph { a [label=\"a:b,c\"] }".to_string();
+        let tokens = tokenize(code).unwrap();
+        assert_eq!(tokens.len(), 9);
+        assert!(tokens.contains(&Token::Identifier("a:b,c".to_string())));
+
+        let value = "{ } [ ] = ; , : -> --";
+        let code = format!("digraph {{ a [label=\"{}\"] }}", value);
+        let tokens = tokenize(code).unwrap();
+        assert_eq!(
+            tokens[5..8],
+            [
+                Token::Delimiter(Delimiter::Equal),
+                Token::Identifier(value.to_string()),
+                Token::Delimiter(Delimiter::ClosedSquareBrace),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lex_spans_after_block_comment() {
+        let (tokens, errors) = lex("/* x\n */ a;");
+        assert!(errors.is_empty());
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(
+            (
+                tokens[0].span.start,
+                tokens[0].span.line,
+                tokens[0].span.col
+            ),
+            (9, 1, 5)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_negative_numerals() {
+        let tokens = tokenize("a -- -1.5 -> -.5; b=-2".to_string()).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("a".to_string()),
+                Token::Delimiter(Delimiter::UndirectedEdge),
+                Token::Identifier("-1.5".to_string()),
+                Token::Delimiter(Delimiter::DirectedEdge),
+                Token::Identifier("-.5".to_string()),
+                Token::Delimiter(Delimiter::Semicolon),
+                Token::Identifier("b".to_string()),
+                Token::Delimiter(Delimiter::Equal),
+                Token::Identifier("-2".to_string()),
+            ]
+        );
+        assert!(tokenize("a -".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_tokenize_identifier_followed_by_quote_fails() {
+        let err = tokenize("graph { a\"b\" }".to_string()).unwrap_err();
+        let err = err.downcast_ref::<TokenizeError>().unwrap();
+        assert_eq!((err.line, err.col), (0, 9));
+        assert_eq!(err.token, "a\"");
+
+        assert!(tokenize("graph { a \"b\" }".to_string()).is_ok());
+        assert!(tokenize("graph { a=\"b\" }".to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_tokenize_plus_numerals() {
+        let options = TokenizeOptions {
+            plus_numerals: true,
+            ..Default::default()
+        };
+        let tokens = tokenize_with_options("a=+3; b=+.5".to_string(), &options).unwrap();
+        assert_eq!(tokens[2], Token::Identifier("+3".to_string()));
+        assert_eq!(tokens[6], Token::Identifier("+.5".to_string()));
+
+        assert!(tokenize_with_options("a=++3;".to_string(), &options).is_err());
+        assert!(tokenize_with_options("a=+-3;".to_string(), &options).is_err());
+        assert!(tokenize_with_options("a=+;".to_string(), &options).is_err());
+        assert!(tokenize("a=+3;".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_tokenize_range_spans_are_absolute() {
+        let code = "graph {\n  a -> \"b c\"\n}";
+        let (full, _) = lex(code);
+        // re-lex from the `->`, which is on the second line
+        let tokens = tokenize_range(code, 12).unwrap();
+        assert_eq!(tokens, full[3..]);
+        assert_eq!(
+            (
+                tokens[0].span.start,
+                tokens[0].span.line,
+                tokens[0].span.col
+            ),
+            (12, 1, 5)
+        );
+
+        let bad = "graph {\n  a -> b!c }";
+        let (_, full_errors) = lex(bad);
+        let err = tokenize_range(bad, 15).unwrap_err();
+        let err = err.downcast_ref::<TokenizeError>().unwrap();
+        assert_eq!(
+            (err.line, err.col),
+            (full_errors[0].line, full_errors[0].col)
+        );
+        assert!(tokenize_range(code, 100).is_err());
+    }
+
+    fn read_in_chunks(code: &[u8], chunk_size: usize) -> Result<Vec<Spanned<Token>>> {
+        tokenize_reader_with_chunk_size(code, chunk_size).collect()
+    }
+
+    #[test]
+    fn test_tokenize_reader_matches_whole_string() {
+        let code = "# 1 \"in.gv\"\ndigraph \"g\" {\n  /* a */ ü -> \"日本 \\\" -> --\" -- b;\n  c->d--e // x\n  f [w=-1.5, l=\"x y\"] /*/ */ }";
+        let expected = tokenize_range(code, 0).unwrap();
+        for chunk_size in 1..=7 {
+            assert_eq!(
+                read_in_chunks(code.as_bytes(), chunk_size).unwrap(),
+                expected,
+                "chunk size {}",
+                chunk_size
+            );
+        }
+        let tokens: Vec<_> = tokenize_reader(code.as_bytes())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_tokenize_reader_errors() {
+        for code in ["a - b", "a -", "a!b -> c", "x\"y\""] {
+            let expected = tokenize_range(code, 0).unwrap_err().to_string();
+            for chunk_size in 1..=3 {
+                let err = read_in_chunks(code.as_bytes(), chunk_size).unwrap_err();
+                assert_eq!(
+                    err.to_string(),
+                    expected,
+                    "{} in chunks of {}",
+                    code,
+                    chunk_size
+                );
+            }
+        }
+
+        // tokens before the error still come out
+        let mut tokens = tokenize_reader_with_chunk_size("a -> b -x".as_bytes(), 2);
+        assert_eq!(
+            tokens.next().unwrap().unwrap().value,
+            Token::Identifier("a".to_string())
+        );
+        assert!(tokens.nth(1).unwrap().is_ok());
+        assert!(tokens.next().unwrap().is_err());
+        assert!(tokens.next().is_none());
+
+        let bad_utf8 = [b'a', b' ', 0xff, b'b'];
+        for chunk_size in 1..=4 {
+            let err = read_in_chunks(&bad_utf8, chunk_size).unwrap_err();
+            assert!(
+                err.to_string().contains("Invalid UTF-8 at byte 2"),
+                "{}",
+                err
+            );
+        }
+        // a multibyte char cut off at the end of the input
+        let err = read_in_chunks(&"a ü".as_bytes()[..3], 1).unwrap_err();
+        assert!(err.to_string().contains("Invalid UTF-8"), "{}", err);
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        let tokens = tokenize("digraph { a -> \"b c\" [w=1] }".to_string()).unwrap();
+        assert_eq!(
+            format_tokens(&tokens),
+            " 0  keyword     digraph
+ 1  delimiter   {
+ 2  identifier  \"a\"
+ 3  delimiter   ->
+ 4  identifier  \"b c\"
+ 5  delimiter   [
+ 6  identifier  \"w\"
+ 7  delimiter   =
+ 8  identifier  \"1\"
+ 9  delimiter   ]
+10  delimiter   }
+"
+        );
+        assert_eq!(format_tokens(&[]), "");
+    }
 }
@@ -0,0 +1,796 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    graph::{collect_edges, Edge},
+    parser::grammer::{
+        AttrStmtKind, Attribute, DotGraph, EdgeOp, EdgeRhs, EdgeStmt, EdgeStmtSide, NodeStmt,
+        Statement,
+    },
+};
+
+pub(crate) fn merge_into(attributes: &mut Vec<Attribute>, updates: &[Attribute]) {
+    for update in updates {
+        match attributes
+            .iter_mut()
+            .find(|attribute| attribute.lhs == update.lhs)
+        {
+            Some(attribute) => attribute.rhs = update.rhs.clone(),
+            None => attributes.push(update.clone()),
+        }
+    }
+}
+
+// Keys from `override_` replace the ones in `base`, new keys are appended in order
+// Repeated keys within either list collapse to their last value too
+pub fn merge_attrs(base: &[Attribute], override_: &[Attribute]) -> Vec<Attribute> {
+    let mut merged = vec![];
+    merge_into(&mut merged, base);
+    merge_into(&mut merged, override_);
+    merged
+}
+
+// Each statement list is merged on its own, so a node declared inside a subgraph stays there
+fn merge_node_statements(statements: &mut Vec<Statement>) {
+    let mut merged: Vec<Statement> = Vec::with_capacity(statements.len());
+    for statement in statements.drain(..) {
+        match statement {
+            Statement::NodeStmt(node_stmt) => {
+                let existing = merged.iter_mut().find_map(|merged_stmt| match merged_stmt {
+                    Statement::NodeStmt(existing) if existing.id.id == node_stmt.id.id => {
+                        Some(existing)
+                    }
+                    _ => None,
+                });
+                match existing {
+                    Some(existing) => merge_node_stmt(existing, node_stmt),
+                    None => merged.push(Statement::NodeStmt(node_stmt)),
+                }
+            }
+            Statement::SubGraph(mut subgraph) => {
+                merge_node_statements(&mut subgraph.statements);
+                merged.push(Statement::SubGraph(subgraph));
+            }
+            other => merged.push(other),
+        }
+    }
+    *statements = merged;
+}
+
+fn merge_node_stmt(existing: &mut NodeStmt, node_stmt: NodeStmt) {
+    if let Some(updates) = node_stmt.attributes {
+        merge_into(existing.attributes.get_or_insert_with(Vec::new), &updates);
+    }
+    if node_stmt.id.port.is_some() {
+        existing.id.port = node_stmt.id.port;
+    }
+}
+
+impl Edge<'_> {
+    // A plain `from -> to [attributes]` statement for this edge
+    pub(crate) fn to_edge_stmt(&self, edge_op: EdgeOp) -> EdgeStmt {
+        EdgeStmt {
+            edge_lhs: EdgeStmtSide::NodeId(self.from.clone()),
+            edge_rhs: EdgeRhs {
+                edge_op,
+                edge_to: EdgeStmtSide::NodeId(self.to.clone()),
+                edge_optional: None,
+            },
+            attributes: if self.attributes.is_empty() {
+                None
+            } else {
+                Some(self.attributes.to_vec())
+            },
+        }
+    }
+}
+
+// Node statements inside an edge side subgraph only carry information when they have attributes
+fn retain_side_node_stmts(side: &EdgeStmtSide, keep: &dyn Fn(&str) -> bool) -> Vec<Statement> {
+    let EdgeStmtSide::SubGraph(subgraph) = side else {
+        return vec![];
+    };
+    let mut retained = vec![];
+    for statement in &subgraph.statements {
+        match statement {
+            Statement::NodeStmt(node_stmt)
+                if node_stmt.attributes.is_some() && keep(&node_stmt.id.id) =>
+            {
+                retained.push(statement.clone())
+            }
+            Statement::SubGraph(inner) => retained.extend(retain_side_node_stmts(
+                &EdgeStmtSide::SubGraph(inner.clone()),
+                keep,
+            )),
+            _ => {}
+        }
+    }
+    retained
+}
+
+// Copies the statements keeping only nodes accepted by `keep`
+// Edge statements are expanded into one statement per edge and kept when both ends are kept
+fn retain_statements(
+    statements: &[Statement],
+    keep: &dyn Fn(&str) -> bool,
+    edge_op: &EdgeOp,
+) -> Vec<Statement> {
+    let mut retained = vec![];
+    for statement in statements {
+        match statement {
+            Statement::NodeStmt(node_stmt) => {
+                if keep(&node_stmt.id.id) {
+                    retained.push(statement.clone());
+                }
+            }
+            Statement::EdgeStmt(edge_stmt) => {
+                for side in crate::graph::edge_sides(edge_stmt) {
+                    retained.extend(retain_side_node_stmts(side, keep));
+                }
+                let mut edges = vec![];
+                collect_edges(std::slice::from_ref(statement), &mut edges);
+                for edge in edges {
+                    if keep(&edge.from.id) && keep(&edge.to.id) {
+                        retained.push(Statement::EdgeStmt(edge.to_edge_stmt(edge_op.clone())));
+                    }
+                }
+            }
+            Statement::SubGraph(subgraph) => {
+                let mut subgraph = subgraph.clone();
+                subgraph.statements = retain_statements(&subgraph.statements, keep, edge_op);
+                retained.push(Statement::SubGraph(subgraph));
+            }
+            Statement::AttrStmt(_) | Statement::AttributeStmt(_) => {
+                retained.push(statement.clone())
+            }
+        }
+    }
+    retained
+}
+
+// Which edges DotGraph::ego_graph follows out of a node, undirected graphs always use Both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EgoDirection {
+    Out,
+    In,
+    Both,
+}
+
+// a -> b -> c becomes c -> b -> a, the statement attributes stay with it
+fn reverse_edge_stmt(edge_stmt: &mut EdgeStmt) {
+    let mut sides = vec![edge_stmt.edge_lhs.clone()];
+    let mut ops = vec![];
+    let mut rhs: Option<&EdgeRhs> = Some(&edge_stmt.edge_rhs);
+    while let Some(current) = rhs {
+        ops.push(current.edge_op.clone());
+        sides.push(current.edge_to.clone());
+        rhs = current.edge_optional.as_deref();
+    }
+
+    let mut sides = sides.into_iter().rev();
+    // an edge statement always has a lhs and at least one rhs
+    let Some(edge_lhs) = sides.next() else {
+        return;
+    };
+    let mut edge_rhs: Option<EdgeRhs> = None;
+    for (edge_op, edge_to) in ops.into_iter().zip(sides).rev() {
+        edge_rhs = Some(EdgeRhs {
+            edge_op,
+            edge_to,
+            edge_optional: edge_rhs.map(Box::new),
+        });
+    }
+    if let Some(edge_rhs) = edge_rhs {
+        edge_stmt.edge_lhs = edge_lhs;
+        edge_stmt.edge_rhs = edge_rhs;
+    }
+}
+
+fn reverse_statements(statements: &mut [Statement]) {
+    for statement in statements {
+        match statement {
+            Statement::EdgeStmt(edge_stmt) => reverse_edge_stmt(edge_stmt),
+            Statement::SubGraph(subgraph) => reverse_statements(&mut subgraph.statements),
+            _ => {}
+        }
+    }
+}
+
+// a -> b -> c [attrs] becomes a -> b [attrs] and b -> c [attrs]
+fn split_edge_chain(edge_stmt: EdgeStmt) -> Vec<EdgeStmt> {
+    let EdgeStmt {
+        mut edge_lhs,
+        edge_rhs,
+        attributes,
+    } = edge_stmt;
+    let mut pairs = vec![];
+    let mut rhs = Some(edge_rhs);
+    while let Some(current) = rhs {
+        pairs.push(EdgeStmt {
+            edge_lhs,
+            edge_rhs: EdgeRhs {
+                edge_op: current.edge_op,
+                edge_to: current.edge_to.clone(),
+                edge_optional: None,
+            },
+            attributes: attributes.clone(),
+        });
+        edge_lhs = current.edge_to;
+        rhs = current.edge_optional.map(|next| *next);
+    }
+    pairs
+}
+
+fn expand_side_chains(side: &mut EdgeStmtSide) {
+    if let EdgeStmtSide::SubGraph(subgraph) = side {
+        expand_chains(&mut subgraph.statements);
+    }
+}
+
+fn expand_chains(statements: &mut Vec<Statement>) {
+    let mut expanded = Vec::with_capacity(statements.len());
+    for statement in statements.drain(..) {
+        match statement {
+            Statement::EdgeStmt(edge_stmt) => {
+                for mut pair in split_edge_chain(edge_stmt) {
+                    expand_side_chains(&mut pair.edge_lhs);
+                    expand_side_chains(&mut pair.edge_rhs.edge_to);
+                    expanded.push(Statement::EdgeStmt(pair));
+                }
+            }
+            Statement::SubGraph(mut subgraph) => {
+                expand_chains(&mut subgraph.statements);
+                expanded.push(Statement::SubGraph(subgraph));
+            }
+            other => expanded.push(other),
+        }
+    }
+    *statements = expanded;
+}
+
+// Duplicate keys collapse to the last value, then keys are sorted
+fn canonicalize_attributes(attributes: &mut Vec<Attribute>) {
+    let mut merged = vec![];
+    merge_into(&mut merged, attributes);
+    merged.sort_by(|a, b| a.lhs.cmp(&b.lhs));
+    *attributes = merged;
+}
+
+fn canonicalize_side(side: &mut EdgeStmtSide) {
+    if let EdgeStmtSide::SubGraph(subgraph) = side {
+        sort_statements(&mut subgraph.statements);
+    }
+}
+
+// Graph attributes, then nodes by id, then edges by their text
+fn statement_sort_key(statement: &Statement) -> (u8, String) {
+    match statement {
+        Statement::AttributeStmt(attribute_stmt) => (0, attribute_stmt.lhs.clone()),
+        Statement::AttrStmt(_) => (0, String::new()),
+        Statement::NodeStmt(node_stmt) => (1, node_stmt.id.id.clone()),
+        Statement::EdgeStmt(_) => (2, statement.to_string()),
+        Statement::SubGraph(_) => (3, String::new()),
+    }
+}
+
+// node [...], edge [...] and subgraphs change what the statements after them mean,
+// so they stay where they are and only the statements between them get sorted
+fn is_sort_boundary(statement: &Statement) -> bool {
+    match statement {
+        Statement::AttrStmt(attr_stmt) => attr_stmt.kind != AttrStmtKind::Graph,
+        Statement::SubGraph(_) => true,
+        _ => false,
+    }
+}
+
+fn sort_statements(statements: &mut Vec<Statement>) {
+    for statement in statements.iter_mut() {
+        match statement {
+            Statement::NodeStmt(node_stmt) => {
+                if let Some(attributes) = &mut node_stmt.attributes {
+                    canonicalize_attributes(attributes);
+                }
+            }
+            Statement::EdgeStmt(edge_stmt) => {
+                if let Some(attributes) = &mut edge_stmt.attributes {
+                    canonicalize_attributes(attributes);
+                }
+                canonicalize_side(&mut edge_stmt.edge_lhs);
+                canonicalize_side(&mut edge_stmt.edge_rhs.edge_to);
+            }
+            Statement::AttrStmt(attr_stmt) => {
+                canonicalize_attributes(&mut attr_stmt.attr_list.items)
+            }
+            Statement::SubGraph(subgraph) => sort_statements(&mut subgraph.statements),
+            Statement::AttributeStmt(_) => {}
+        }
+    }
+
+    let mut sorted = Vec::with_capacity(statements.len());
+    let mut segment: Vec<Statement> = vec![];
+    for statement in statements.drain(..) {
+        if is_sort_boundary(&statement) {
+            segment.sort_by_cached_key(statement_sort_key);
+            sorted.append(&mut segment);
+            sorted.push(statement);
+        } else {
+            segment.push(statement);
+        }
+    }
+    segment.sort_by_cached_key(statement_sort_key);
+    sorted.append(&mut segment);
+    *statements = sorted;
+}
+
+impl DotGraph {
+    pub(crate) fn retain_nodes(&self, keep: &dyn Fn(&str) -> bool) -> DotGraph {
+        let edge_op = if self.is_directed() {
+            EdgeOp::Directed
+        } else {
+            EdgeOp::UnDirected
+        };
+        DotGraph {
+            graph_type: self.graph_type.clone(),
+            strict_mode: self.strict_mode,
+            id: self.id.clone(),
+            statements: self
+                .statements
+                .as_ref()
+                .map(|statements| retain_statements(statements, keep, &edge_op)),
+        }
+    }
+
+    // One standalone graph per connected component, weakly connected for digraphs
+    // Each keeps the graph type, strictness, id and the statements that touch its nodes
+    pub fn split_components(&self) -> Vec<DotGraph> {
+        self.connected_components()
+            .iter()
+            .map(|component| self.retain_nodes(&|id| component.iter().any(|node| node == id)))
+            .collect()
+    }
+
+    // The given nodes and every edge with both ends among them, attributes kept
+    // Ids that are not in the graph are ignored
+    pub fn induced_subgraph(&self, nodes: &[&str]) -> DotGraph {
+        self.retain_nodes(&|id| nodes.contains(&id))
+    }
+
+    // The nodes at most `radius` edges away from `center` and every edge between them
+    // Keeps the graph type, id and attribute statements like split_components, an unknown center
+    // gives a graph without nodes
+    pub fn ego_graph(&self, center: &str, radius: usize, direction: EgoDirection) -> DotGraph {
+        let direction = if self.is_directed() {
+            direction
+        } else {
+            EgoDirection::Both
+        };
+        let mut neighbors: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in self.edges() {
+            let (from, to) = (edge.from.id.as_str(), edge.to.id.as_str());
+            if direction != EgoDirection::In {
+                neighbors.entry(from).or_default().push(to);
+            }
+            if direction != EgoDirection::Out {
+                neighbors.entry(to).or_default().push(from);
+            }
+        }
+
+        let mut within: HashSet<&str> = HashSet::new();
+        if self.node_ids().iter().any(|node_id| node_id.id == center) {
+            within.insert(center);
+        }
+        let mut queue: VecDeque<(&str, usize)> = within.iter().map(|id| (*id, 0)).collect();
+        while let Some((id, distance)) = queue.pop_front() {
+            if distance == radius {
+                continue;
+            }
+            for neighbor in neighbors.get(id).into_iter().flatten() {
+                if within.insert(neighbor) {
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+        self.retain_nodes(&|id| within.contains(id))
+    }
+
+    // New graph with the nodes matching `pred` and the edges between them
+    // Nodes that never get a node statement can not match and are dropped
+    pub fn filter_nodes<F: Fn(&NodeStmt) -> bool>(&self, pred: F) -> DotGraph {
+        let mut matching: Vec<&str> = vec![];
+        let mut stack: Vec<&[Statement]> = vec![self.statements.as_deref().unwrap_or_default()];
+        while let Some(statements) = stack.pop() {
+            for statement in statements {
+                match statement {
+                    Statement::NodeStmt(node_stmt) if pred(node_stmt) => {
+                        matching.push(&node_stmt.id.id)
+                    }
+                    Statement::SubGraph(subgraph) => stack.push(&subgraph.statements),
+                    Statement::EdgeStmt(edge_stmt) => {
+                        for side in crate::graph::edge_sides(edge_stmt) {
+                            if let EdgeStmtSide::SubGraph(subgraph) = side {
+                                stack.push(&subgraph.statements);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.retain_nodes(&|id| matching.contains(&id))
+    }
+
+    // Same graph with every directed edge pointing the other way, undirected graphs are returned as is
+    pub fn reverse(&self) -> DotGraph {
+        let mut reversed = self.clone();
+        if self.is_directed() {
+            if let Some(statements) = &mut reversed.statements {
+                reverse_statements(statements);
+            }
+        }
+        reversed
+    }
+
+    // Splits every edge chain into one statement per hop, each keeping the chain's attributes
+    // Subgraph endpoints are kept as they are
+    pub fn expand_edge_chains(&mut self) {
+        if let Some(statements) = &mut self.statements {
+            expand_chains(statements);
+        }
+    }
+
+    // Deterministic form for diffing and caching: duplicate node declarations merged,
+    // edge chains split, attributes sorted by key and statements sorted
+    pub fn canonicalize(&self) -> DotGraph {
+        let mut canonical = self.clone();
+        canonical.merge_node_declarations();
+        canonical.expand_edge_chains();
+        if let Some(statements) = &mut canonical.statements {
+            sort_statements(statements);
+        }
+        canonical
+    }
+
+    // Collapses repeated node statements for the same id into the first one, later attributes win
+    pub fn merge_node_declarations(&mut self) {
+        if let Some(statements) = &mut self.statements {
+            merge_node_statements(statements);
+        }
+    }
+}
+
+// Compares and hashes graphs by their canonical form, so reordered but equivalent graphs
+// are the same key. The derived PartialEq on DotGraph stays order-sensitive
+#[derive(Debug, Clone)]
+pub struct SemanticGraph(pub DotGraph);
+
+impl SemanticGraph {
+    fn canonical_text(&self) -> String {
+        self.0.canonicalize().to_string()
+    }
+}
+
+impl PartialEq for SemanticGraph {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical_text() == other.canonical_text()
+    }
+}
+
+impl Eq for SemanticGraph {}
+
+impl Hash for SemanticGraph {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical_text().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::{merge_attrs, EgoDirection, SemanticGraph};
+    use crate::parser::grammer::{Attribute, DotGraph};
+
+    #[test]
+    fn test_merge_node_declarations() {
+        let code = "graph { a [x=1]; b; a [y=2]; a [x=3] }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        dg.merge_node_declarations();
+        assert_eq!(
+            dg.to_string(),
+            "graph {
+    a [x=3, y=2];
+    b;
+}"
+        );
+    }
+
+    #[test]
+    fn test_merge_node_declarations_per_subgraph() {
+        let code = "graph { a; subgraph s { a [x=1]; a [y=2] } }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        dg.merge_node_declarations();
+        assert_eq!(
+            dg.to_string(),
+            "graph {
+    a;
+    subgraph s {
+        a [x=1, y=2];
+    }
+}"
+        );
+    }
+
+    #[test]
+    fn test_filter_nodes_by_attribute() {
+        let code = "digraph {
+            api [kind=service]; db [kind=storage]; web [kind=service];
+            subgraph cluster_0 { worker [kind=service] }
+            web -> api -> db; api -> worker; web -> {api worker} [color=red]
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let filtered = dg.filter_nodes(|node| {
+            node.attributes
+                .iter()
+                .flatten()
+                .any(|attr| attr.lhs == "kind" && attr.rhs == "service")
+        });
+        assert_eq!(
+            filtered.to_string(),
+            "digraph {
+    api [kind=service];
+    web [kind=service];
+    subgraph cluster_0 {
+        worker [kind=service];
+    }
+    web -> api;
+    api -> worker;
+    web -> api [color=red];
+    web -> worker [color=red];
+}"
+        );
+    }
+
+    #[test]
+    fn test_reverse_digraph() {
+        let code = "digraph { a -> b -> c [color=red]; subgraph s { d -> {e f} } }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let reversed = dg.reverse();
+        let pairs: Vec<(&str, &str, usize)> = reversed
+            .edges()
+            .iter()
+            .map(|edge| {
+                (
+                    edge.from.id.as_str(),
+                    edge.to.id.as_str(),
+                    edge.attributes.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("c", "b", 1), ("b", "a", 1), ("e", "d", 0), ("f", "d", 0)]
+        );
+        assert_eq!(
+            reversed.to_string(),
+            "digraph {
+    c -> b -> a [color=red];
+    subgraph s {
+        {e; f} -> d;
+    }
+}"
+        );
+        assert_eq!(reversed.reverse(), dg);
+    }
+
+    #[test]
+    fn test_reverse_undirected_is_unchanged() {
+        let code = "graph { a -- b -- c }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(dg.reverse(), dg);
+    }
+
+    #[test]
+    fn test_expand_edge_chains() {
+        let code = "digraph { a -> b -> {c d} -> e [color=red]; subgraph s { f -> g -> h } }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let edges_before: Vec<String> = dg.edges().iter().map(|e| format!("{:?}", e)).collect();
+        dg.expand_edge_chains();
+        assert_eq!(dg.statements.as_ref().unwrap().len(), 4);
+        assert_eq!(
+            dg.to_string(),
+            "digraph {
+    a -> b [color=red];
+    b -> {c; d} [color=red];
+    {c; d} -> e [color=red];
+    subgraph s {
+        f -> g;
+        g -> h;
+    }
+}"
+        );
+        let edges_after: Vec<String> = dg.edges().iter().map(|e| format!("{:?}", e)).collect();
+        assert_eq!(edges_after, edges_before);
+    }
+
+    #[test]
+    fn test_canonicalize_equivalent_graphs() {
+        let first = "digraph G {
+            rankdir=LR; a [label=a]; b [shape=box]; a -> b -> c [weight=1, color=red];
+            node [color=blue]; d; subgraph s { y; x }
+        }";
+        let second = "digraph G {
+            b -> c [color=red, weight=1]; b; rankdir=LR; a -> b [weight=1, color=red];
+            a [label=\"a\"]; b [shape=box];
+            node [color=blue]; d; subgraph s { x; y }
+        }";
+        let canonical = |code: &str| {
+            parse(&tokenize(code.to_string()).unwrap())
+                .unwrap()
+                .canonicalize()
+                .to_string()
+        };
+        assert_eq!(canonical(first), canonical(second));
+        assert_eq!(
+            canonical(second),
+            "digraph G {
+    rankdir=LR;
+    a [label=a];
+    b [shape=box];
+    a -> b [color=red, weight=1];
+    b -> c [color=red, weight=1];
+    node [color=blue];
+    d;
+    subgraph s {
+        x;
+        y;
+    }
+}"
+        );
+    }
+
+    #[test]
+    fn test_semantic_graph_as_set_key() {
+        let graph = |code: &str| parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let first = graph("digraph { a -> b; b [color=red, shape=box]; rankdir=LR }");
+        let second = graph("digraph { rankdir=LR; b [shape=box, color=red]; a -> b }");
+        let different = graph("digraph { b -> a; b [color=red, shape=box]; rankdir=LR }");
+        assert_ne!(first, second);
+
+        let mut set = HashSet::new();
+        set.insert(SemanticGraph(first));
+        set.insert(SemanticGraph(second));
+        assert_eq!(set.len(), 1);
+        set.insert(SemanticGraph(different));
+        assert_eq!(set.len(), 2);
+    }
+
+    fn attrs(pairs: &[(&str, &str)]) -> Vec<Attribute> {
+        pairs
+            .iter()
+            .map(|(lhs, rhs)| Attribute {
+                lhs: lhs.to_string(),
+                rhs: rhs.to_string(),
+                quoted_lhs: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_attrs_overlapping_keys() {
+        let base = attrs(&[("color", "red"), ("shape", "box")]);
+        let override_ = attrs(&[("color", "blue"), ("label", "x")]);
+        assert_eq!(
+            merge_attrs(&base, &override_),
+            attrs(&[("color", "blue"), ("shape", "box"), ("label", "x")])
+        );
+        assert_eq!(
+            merge_attrs(&attrs(&[("a", "1"), ("a", "2")]), &[]),
+            attrs(&[("a", "2")])
+        );
+    }
+
+    #[test]
+    fn test_merge_attrs_disjoint_keys() {
+        let base = attrs(&[("color", "red")]);
+        let override_ = attrs(&[("shape", "box")]);
+        assert_eq!(
+            merge_attrs(&base, &override_),
+            attrs(&[("color", "red"), ("shape", "box")])
+        );
+        assert_eq!(merge_attrs(&[], &override_), override_);
+    }
+
+    #[test]
+    fn test_split_components() {
+        let code = "strict digraph G { rankdir=LR; a -> b; c -> d; b -> e; f; d [color=red] }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let components = dg.split_components();
+        let node_sets: Vec<Vec<String>> = components
+            .iter()
+            .map(|component| component.node_ids_in_order())
+            .collect();
+        assert_eq!(
+            node_sets,
+            vec![vec!["a", "b", "e"], vec!["c", "d"], vec!["f"]]
+        );
+        assert!(components.iter().all(|component| component.strict_mode));
+        assert_eq!(
+            components[1].to_string(),
+            "strict digraph G {
+    rankdir=LR;
+    c -> d;
+    d [color=red];
+}"
+        );
+        assert!(parse(&tokenize("graph { }".to_string()).unwrap())
+            .unwrap()
+            .split_components()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_induced_subgraph() {
+        let code = "digraph G {
+            node [shape=box];
+            a [color=red]; a -> b [weight=3]; b -> c; c -> a; c -> d; d -> e
+            subgraph cluster_0 { b; e }
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(
+            dg.induced_subgraph(&["a", "b", "c", "missing"]).to_string(),
+            "digraph G {
+    node [shape=box];
+    a [color=red];
+    a -> b [weight=3];
+    b -> c;
+    c -> a;
+    subgraph cluster_0 {
+        b;
+    }
+}"
+        );
+        assert!(dg.induced_subgraph(&[]).edges().is_empty());
+    }
+
+    #[test]
+    fn test_ego_graph() {
+        let code = "digraph G {
+            rankdir=LR; hub [color=red];
+            a -> hub -> b -> c; d -> a; hub -> hub; e
+        }";
+        let dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        assert_eq!(
+            dg.ego_graph("hub", 1, EgoDirection::Both).to_string(),
+            "digraph G {
+    rankdir=LR;
+    hub [color=red];
+    a -> hub;
+    hub -> b;
+    hub -> hub;
+}"
+        );
+        let ids = |ego: DotGraph| ego.node_ids_in_order();
+        assert_eq!(
+            ids(dg.ego_graph("hub", 1, EgoDirection::Out)),
+            vec!["hub", "b"]
+        );
+        assert_eq!(
+            ids(dg.ego_graph("hub", 2, EgoDirection::In)),
+            vec!["hub", "a", "d"]
+        );
+        assert_eq!(ids(dg.ego_graph("hub", 0, EgoDirection::Both)), vec!["hub"]);
+        assert_eq!(ids(dg.ego_graph("e", 3, EgoDirection::Both)), vec!["e"]);
+        assert!(ids(dg.ego_graph("missing", 1, EgoDirection::Both)).is_empty());
+
+        // direction does not matter without directed edges
+        let undirected = parse(&tokenize("graph { a -- b -- c }".to_string()).unwrap()).unwrap();
+        assert_eq!(
+            ids(undirected.ego_graph("c", 1, EgoDirection::Out)),
+            vec!["b", "c"]
+        );
+    }
+}
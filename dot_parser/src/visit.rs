@@ -0,0 +1,142 @@
+use crate::parser::grammer::{
+    Attribute, DotGraph, EdgeRhs, EdgeStmtSide, NodeId, Statement, SubGraph,
+};
+
+// Hooks called by walk_mut, every one defaults to doing nothing
+// Statements are visited before their contents, so a replaced statement gets walked as replaced
+// `k=v` statements only reach visit_statement, they hold no Attribute
+pub trait VisitorMut {
+    fn visit_statement(&mut self, _statement: &mut Statement) {}
+
+    fn visit_subgraph(&mut self, _subgraph: &mut SubGraph) {}
+
+    // Node statements and every node in an edge, including subgraph endpoints
+    fn visit_node_id(&mut self, _node_id: &mut NodeId) {}
+
+    // Attributes of node, edge and `graph|node|edge [...]` statements
+    fn visit_attribute(&mut self, _attribute: &mut Attribute) {}
+}
+
+fn walk_attributes<V: VisitorMut>(attributes: &mut [Attribute], visitor: &mut V) {
+    for attribute in attributes {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+fn walk_subgraph<V: VisitorMut>(subgraph: &mut SubGraph, visitor: &mut V) {
+    visitor.visit_subgraph(subgraph);
+    walk_statements(&mut subgraph.statements, visitor);
+}
+
+fn walk_side<V: VisitorMut>(side: &mut EdgeStmtSide, visitor: &mut V) {
+    match side {
+        EdgeStmtSide::NodeId(node_id) => visitor.visit_node_id(node_id),
+        EdgeStmtSide::SubGraph(subgraph) => walk_subgraph(subgraph, visitor),
+    }
+}
+
+fn walk_statements<V: VisitorMut>(statements: &mut [Statement], visitor: &mut V) {
+    for statement in statements {
+        visitor.visit_statement(statement);
+        match statement {
+            Statement::NodeStmt(node_stmt) => {
+                visitor.visit_node_id(&mut node_stmt.id);
+                walk_attributes(
+                    node_stmt.attributes.as_deref_mut().unwrap_or_default(),
+                    visitor,
+                );
+            }
+            Statement::EdgeStmt(edge_stmt) => {
+                walk_side(&mut edge_stmt.edge_lhs, visitor);
+                let mut rhs: Option<&mut EdgeRhs> = Some(&mut edge_stmt.edge_rhs);
+                while let Some(current) = rhs {
+                    walk_side(&mut current.edge_to, visitor);
+                    rhs = current.edge_optional.as_deref_mut();
+                }
+                walk_attributes(
+                    edge_stmt.attributes.as_deref_mut().unwrap_or_default(),
+                    visitor,
+                );
+            }
+            Statement::AttrStmt(attr_stmt) => {
+                walk_attributes(&mut attr_stmt.attr_list.items, visitor)
+            }
+            Statement::SubGraph(subgraph) => walk_subgraph(subgraph, visitor),
+            Statement::AttributeStmt(_) => {}
+        }
+    }
+}
+
+// Depth-first, document ordered walk over every statement of the graph
+pub fn walk_mut<V: VisitorMut>(dg: &mut DotGraph, visitor: &mut V) {
+    if let Some(statements) = &mut dg.statements {
+        walk_statements(statements, visitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tokenizer::tokenize};
+
+    use super::*;
+
+    struct UppercaseIds;
+
+    impl VisitorMut for UppercaseIds {
+        fn visit_node_id(&mut self, node_id: &mut NodeId) {
+            node_id.id = node_id.id.to_uppercase();
+        }
+    }
+
+    #[test]
+    fn test_walk_mut_uppercases_node_ids() {
+        let code = "digraph { a [label=a]; a -> b:p; subgraph s { c -> {d e} } }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        walk_mut(&mut dg, &mut UppercaseIds);
+        assert_eq!(
+            dg.to_string(),
+            "digraph {
+    A [label=a];
+    A -> B:p;
+    subgraph s {
+        C -> {D; E};
+    }
+}"
+        );
+    }
+
+    #[derive(Default)]
+    struct CountVisits {
+        statements: usize,
+        subgraphs: usize,
+        attributes: Vec<String>,
+    }
+
+    impl VisitorMut for CountVisits {
+        fn visit_statement(&mut self, _statement: &mut Statement) {
+            self.statements += 1;
+        }
+
+        fn visit_subgraph(&mut self, _subgraph: &mut SubGraph) {
+            self.subgraphs += 1;
+        }
+
+        fn visit_attribute(&mut self, attribute: &mut Attribute) {
+            self.attributes.push(attribute.lhs.clone());
+            attribute.rhs = attribute.rhs.to_uppercase();
+        }
+    }
+
+    #[test]
+    fn test_walk_mut_visits_everything() {
+        let code = "graph { rankdir=LR; node [shape=box]; a [color=red]; {b} -- c [style=dashed] }";
+        let mut dg = parse(&tokenize(code.to_string()).unwrap()).unwrap();
+        let mut visitor = CountVisits::default();
+        walk_mut(&mut dg, &mut visitor);
+        assert_eq!(visitor.statements, 5);
+        assert_eq!(visitor.subgraphs, 1);
+        assert_eq!(visitor.attributes, vec!["shape", "color", "style"]);
+        assert_eq!(dg.graph_attrs()["rankdir"], "LR");
+        assert!(dg.to_string().contains("a [color=RED]"));
+    }
+}